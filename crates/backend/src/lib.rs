@@ -0,0 +1,6 @@
+//! Backend side helpers for the `tauri-global-sys` crate.
+
+/// Emit items to a frontend stream created with `tauri_global_sys::tauri::invoke_stream`.
+pub mod stream;
+
+pub use stream::StreamSender;
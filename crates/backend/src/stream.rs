@@ -0,0 +1,79 @@
+use serde::Serialize;
+use tauri::{Runtime, Window};
+
+/// The message sent on a stream event.
+///
+/// This must stay in sync with `tauri_global_sys::tauri::stream::StreamMessage`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+enum StreamMessage<T> {
+    Item(T),
+    End,
+    Error(String),
+}
+
+/// Sends the items of a streaming command to the window that invoked it.
+///
+/// ```rust,ignore
+/// #[tauri::command]
+/// async fn tail_log(window: tauri::Window, stream_channel: String) -> Result<(), String> {
+///     let sender = StreamSender::new(window, stream_channel);
+///     for line in read_lines() {
+///         sender.send(&line).map_err(|e| e.to_string())?;
+///     }
+///     sender.end().map_err(|e| e.to_string())
+/// }
+/// ```
+///
+/// If the sender is dropped without calling [`end`](Self::end) or [`error`](Self::error),
+/// the stream is ended automatically.
+pub struct StreamSender<R: Runtime> {
+    window: Window<R>,
+    channel: String,
+    finished: bool,
+}
+
+impl<R: Runtime> StreamSender<R> {
+    /// `channel` is the `stream_channel` argument received by the command.
+    pub fn new(window: Window<R>, channel: String) -> Self {
+        Self {
+            window,
+            channel,
+            finished: false,
+        }
+    }
+
+    /// The event name the items are emitted on.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    fn emit<T: Serialize + Clone>(&self, message: StreamMessage<T>) -> tauri::Result<()> {
+        self.window.emit(&self.channel, message)
+    }
+
+    /// Send a new item.
+    pub fn send<T: Serialize>(&self, item: &T) -> tauri::Result<()> {
+        self.emit(StreamMessage::Item(item))
+    }
+
+    /// End the stream.
+    pub fn end(mut self) -> tauri::Result<()> {
+        self.finished = true;
+        self.emit(StreamMessage::<()>::End)
+    }
+
+    /// Fail the stream with an error message.
+    pub fn error<E: ToString>(mut self, error: E) -> tauri::Result<()> {
+        self.finished = true;
+        self.emit(StreamMessage::<()>::Error(error.to_string()))
+    }
+}
+
+impl<R: Runtime> Drop for StreamSender<R> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.emit(StreamMessage::<()>::End);
+        }
+    }
+}
@@ -21,6 +21,9 @@
 
 use enum_all_variants::AllVariants;
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::session;
 
 pub mod raw;
 
@@ -202,7 +205,11 @@ pub struct SaveDialogOptions {
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/dialog/#ask>
 pub async fn ask(message: &str, options: Option<ConfirmDialogOptions>) -> crate::Result<bool> {
-    let res = raw::ask(message, serde_wasm_bindgen::to_value(&options)?).await?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("dialog", "ask", &[&message.into(), &options], async {
+        raw::ask(message, options.clone()).await
+    })
+    .await?;
     Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
@@ -210,7 +217,11 @@ pub async fn ask(message: &str, options: Option<ConfirmDialogOptions>) -> crate:
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/dialog/#confirm>
 pub async fn confirm(message: &str, options: Option<ConfirmDialogOptions>) -> crate::Result<bool> {
-    let res = raw::confirm(message, serde_wasm_bindgen::to_value(&options)?).await?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("dialog", "confirm", &[&message.into(), &options], async {
+        raw::confirm(message, options.clone()).await
+    })
+    .await?;
     Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
@@ -218,7 +229,13 @@ pub async fn confirm(message: &str, options: Option<ConfirmDialogOptions>) -> cr
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/dialog/#message>
 pub async fn message(message: &str, options: Option<MessageDialogOptions>) -> crate::Result<()> {
-    raw::message(message, serde_wasm_bindgen::to_value(&options)?).await?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call("dialog", "message", &[&message.into(), &options], async {
+        raw::message(message, options.clone())
+            .await
+            .map(|_| JsValue::UNDEFINED)
+    })
+    .await?;
     Ok(())
 }
 
@@ -260,7 +277,11 @@ impl OpenReturnType {
 /// You can save it to the filesystem using [`tauri-plugin-persisted-scope`](https://github.com/tauri-apps/plugins-workspace/tree/v1/plugins/persisted-scope).
 ///
 pub async fn open(options: Option<OpenDialogOptions>) -> crate::Result<Option<OpenReturnType>> {
-    let res = raw::open(serde_wasm_bindgen::to_value(&options)?).await?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("dialog", "open", &[&options], async {
+        raw::open(options.clone()).await
+    })
+    .await?;
     Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
@@ -273,7 +294,11 @@ pub async fn open(options: Option<OpenDialogOptions>) -> crate::Result<Option<Op
 /// You can save it to the filesystem using [`tauri-plugin-persisted-scope`](https://github.com/tauri-apps/plugins-workspace/tree/v1/plugins/persisted-scope).
 ///
 pub async fn save(options: Option<SaveDialogOptions>) -> crate::Result<Option<String>> {
-    let res = raw::save(serde_wasm_bindgen::to_value(&options)?).await?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("dialog", "save", &[&options], async {
+        raw::save(options.clone()).await
+    })
+    .await?;
     Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
@@ -2,6 +2,8 @@ use js_sys::JsString;
 use serde::de::DeserializeOwned;
 use wasm_bindgen::{JsCast, JsValue};
 
+use crate::os::OsType;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{0}")]
@@ -20,9 +22,170 @@ pub enum Error {
     Js(js_sys::Error),
     #[error("The current `RawClient` inside the `RawClientWrapper` is empty")]
     RawClientWrapperInnerEmpty,
+    #[error("The operation timed out")]
+    Timeout,
+    #[error("event rpc error: {0}")]
+    EventRpc(String),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("session replay error: {0}")]
+    SessionReplay(String),
+    #[error("invalid path `{path}`: {reason}")]
+    InvalidPath { path: String, reason: &'static str },
+    #[error("invalid glob pattern `{pattern}`: {reason}")]
+    InvalidGlob {
+        pattern: String,
+        reason: &'static str,
+    },
+    #[error("`{0}` already exists")]
+    AlreadyExists(String),
+    #[error("`{path}` is outside of the fs scope (patterns: {})", .patterns.join(", "))]
+    ScopeDenied { path: String, patterns: Vec<String> },
+    #[error("unsupported version {found}, the latest supported version is {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("the text can't be encoded as {0}")]
+    Encoding(&'static str),
+    #[error("`{0}` is outside of the asset protocol scope")]
+    AssetScopeDenied(String),
+    #[error(
+        "failed to load the asset `{url}`{}",
+        .status.map(|status| format!(" (status {status})")).unwrap_or_default()
+    )]
+    AssetLoad { url: String, status: Option<u16> },
+    #[error("failed to install the logger: {0}")]
+    Logger(String),
+    /// A zip archive error, only returned with the `zip` feature.
+    #[error("zip error: {0}")]
+    Zip(String),
+    /// Only returned with the `zip` feature.
+    #[error("the archive extracts to more than {0} bytes")]
+    ArchiveTooLarge(u64),
+    #[error("encryption error: {0}")]
+    Crypto(&'static str),
+}
+
+/// A coarse classification of an [`Error`], mostly useful for the `fs` failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    /// The path is outside of the fs scope configured in tauri.conf.json.
+    ScopeForbidden,
+    NotADirectory,
+    DirectoryNotEmpty,
+    /// Any other OS error.
+    Io,
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify an error message returned by Tauri v1.
+    ///
+    /// The messages end with the OS error (like `No such file or directory (os error 2)`),
+    /// which is classified by its code first, see [`ErrorKind::from_os_error`].
+    /// The Windows messages are told apart by their final period, even when localized.
+    /// The messages without a known code are matched on their English text.
+    pub fn from_message(message: &str) -> Self {
+        let lowercase = message.to_lowercase();
+        let contains = |patterns: &[&str]| patterns.iter().any(|p| lowercase.contains(p));
+        if contains(&["not allowed on the configured scope", "forbidden path"]) {
+            return Self::ScopeForbidden;
+        }
+        let os_error = Self::split_os_error(message);
+        if let Some((description, code)) = os_error {
+            let kind = if description.ends_with('.') {
+                Self::from_os_error(code, OsType::WindowsNt)
+            } else {
+                // Among the classified codes, Linux and macOS only differ on ENOTEMPTY.
+                Self::from_os_error(code, OsType::Linux)
+                    .or_else(|| Self::from_os_error(code, OsType::Darwin))
+            };
+            if let Some(kind) = kind {
+                return kind;
+            }
+        }
+        if contains(&[
+            "no such file or directory",
+            "cannot find the file",
+            "cannot find the path",
+        ]) {
+            Self::NotFound
+        } else if contains(&["file exists", "already exists"]) {
+            Self::AlreadyExists
+        } else if contains(&[
+            "permission denied",
+            "access is denied",
+            "operation not permitted",
+        ]) {
+            Self::PermissionDenied
+        } else if contains(&["not a directory", "directory name is invalid"]) {
+            Self::NotADirectory
+        } else if contains(&["directory not empty", "directory is not empty"]) {
+            Self::DirectoryNotEmpty
+        } else if os_error.is_some() {
+            Self::Io
+        } else {
+            Self::Other
+        }
+    }
+
+    /// The message split before its trailing `(os error N)`, and `N`.
+    fn split_os_error(message: &str) -> Option<(&str, i32)> {
+        let (description, code) = message.trim_end().rsplit_once("(os error ")?;
+        Some((
+            description.trim_end(),
+            code.strip_suffix(')')?.parse().ok()?,
+        ))
+    }
+
+    /// Classify the OS error `code` of `os`, [`None`] for the codes without a matching kind.
+    pub fn from_os_error(code: i32, os: OsType) -> Option<Self> {
+        let kind = match (os, code) {
+            // ERROR_FILE_NOT_FOUND and ERROR_PATH_NOT_FOUND
+            (OsType::WindowsNt, 2 | 3) => Self::NotFound,
+            // ERROR_FILE_EXISTS and ERROR_ALREADY_EXISTS
+            (OsType::WindowsNt, 80 | 183) => Self::AlreadyExists,
+            // ERROR_ACCESS_DENIED
+            (OsType::WindowsNt, 5) => Self::PermissionDenied,
+            // ERROR_DIRECTORY
+            (OsType::WindowsNt, 267) => Self::NotADirectory,
+            // ERROR_DIR_NOT_EMPTY
+            (OsType::WindowsNt, 145) => Self::DirectoryNotEmpty,
+            // ENOENT
+            (OsType::Linux | OsType::Darwin, 2) => Self::NotFound,
+            // EEXIST
+            (OsType::Linux | OsType::Darwin, 17) => Self::AlreadyExists,
+            // EPERM and EACCES
+            (OsType::Linux | OsType::Darwin, 1 | 13) => Self::PermissionDenied,
+            // ENOTDIR
+            (OsType::Linux | OsType::Darwin, 20) => Self::NotADirectory,
+            // ENOTEMPTY
+            (OsType::Linux, 39) | (OsType::Darwin, 66) => Self::DirectoryNotEmpty,
+            _ => return None,
+        };
+        Some(kind)
+    }
 }
 
 impl Error {
+    /// Classify the error, see [`ErrorKind::from_message`] for the Tauri errors.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Tauri(message) => ErrorKind::from_message(message),
+            Self::Js(error) => ErrorKind::from_message(&String::from(error.message())),
+            Self::Invoke(value) => value.as_string().map_or(ErrorKind::Other, |message| {
+                ErrorKind::from_message(&message)
+            }),
+            Self::AlreadyExists(_) => ErrorKind::AlreadyExists,
+            Self::ScopeDenied { .. } | Self::AssetScopeDenied(_) => ErrorKind::ScopeForbidden,
+            Self::AssetLoad {
+                status: Some(404), ..
+            } => ErrorKind::NotFound,
+            _ => ErrorKind::Other,
+        }
+    }
+
     pub(crate) fn tauri(message: String) -> Self {
         Self::Tauri(message)
     }
@@ -59,3 +222,138 @@ impl From<JsValue> for Error {
         }
     }
 }
+
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for Error {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::Zip(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind};
+    use crate::os::OsType;
+
+    fn kind(message: &str) -> ErrorKind {
+        Error::Tauri(message.to_string()).kind()
+    }
+
+    #[test]
+    fn scope() {
+        assert_eq!(
+            kind("path not allowed on the configured scope: /etc/passwd"),
+            ErrorKind::ScopeForbidden
+        );
+        assert_eq!(
+            Error::ScopeDenied {
+                path: "$HOME/a".into(),
+                patterns: vec![]
+            }
+            .kind(),
+            ErrorKind::ScopeForbidden
+        );
+    }
+
+    #[test]
+    fn unix_messages() {
+        assert_eq!(
+            kind("path: /home/me/.config/app/a.json: No such file or directory (os error 2)"),
+            ErrorKind::NotFound
+        );
+        assert_eq!(kind("File exists (os error 17)"), ErrorKind::AlreadyExists);
+        assert_eq!(
+            kind("path: /root/a.txt: Permission denied (os error 13)"),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            kind("Not a directory (os error 20)"),
+            ErrorKind::NotADirectory
+        );
+        assert_eq!(
+            kind("Directory not empty (os error 39)"),
+            ErrorKind::DirectoryNotEmpty
+        );
+        assert_eq!(
+            kind("Directory not empty (os error 66)"),
+            ErrorKind::DirectoryNotEmpty
+        );
+        assert_eq!(kind("Is a directory (os error 21)"), ErrorKind::Io);
+        // EIO, not ERROR_ACCESS_DENIED.
+        assert_eq!(kind("Input/output error (os error 5)"), ErrorKind::Io);
+        // The code wins over a path looking like another error.
+        assert_eq!(
+            kind("path: /tmp/file exists/a: No such file or directory (os error 2)"),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn os_errors() {
+        assert_eq!(
+            ErrorKind::from_os_error(39, OsType::Linux),
+            Some(ErrorKind::DirectoryNotEmpty)
+        );
+        assert_eq!(ErrorKind::from_os_error(39, OsType::Darwin), None);
+        assert_eq!(ErrorKind::from_os_error(39, OsType::WindowsNt), None);
+        assert_eq!(
+            ErrorKind::from_os_error(66, OsType::Darwin),
+            Some(ErrorKind::DirectoryNotEmpty)
+        );
+        assert_eq!(ErrorKind::from_os_error(66, OsType::Linux), None);
+        assert_eq!(
+            ErrorKind::from_os_error(80, OsType::WindowsNt),
+            Some(ErrorKind::AlreadyExists)
+        );
+        assert_eq!(ErrorKind::from_os_error(80, OsType::Linux), None);
+        assert_eq!(
+            ErrorKind::from_os_error(13, OsType::Linux),
+            Some(ErrorKind::PermissionDenied)
+        );
+        assert_eq!(ErrorKind::from_os_error(13, OsType::WindowsNt), None);
+    }
+
+    #[test]
+    fn windows_messages() {
+        assert_eq!(
+            kind(
+                r"path: C:\Users\me\a.txt: The system cannot find the file specified. (os error 2)"
+            ),
+            ErrorKind::NotFound
+        );
+        assert_eq!(
+            kind("The system cannot find the path specified. (os error 3)"),
+            ErrorKind::NotFound
+        );
+        assert_eq!(
+            kind("Cannot create a file when that file already exists. (os error 183)"),
+            ErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            kind("Access is denied. (os error 5)"),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            kind("The directory name is invalid. (os error 267)"),
+            ErrorKind::NotADirectory
+        );
+        assert_eq!(
+            kind("The directory is not empty. (os error 145)"),
+            ErrorKind::DirectoryNotEmpty
+        );
+    }
+
+    #[test]
+    fn localized_messages() {
+        assert_eq!(
+            kind("Le fichier spécifié est introuvable. (os error 2)"),
+            ErrorKind::NotFound
+        );
+        assert_eq!(
+            kind("Accès refusé. (os error 5)"),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(kind("Unbekannter Fehler (os error 1117)"), ErrorKind::Io);
+        assert_eq!(kind("unexpected failure"), ErrorKind::Other);
+    }
+}
@@ -8,9 +8,15 @@ use futures::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+pub mod adapters;
+pub mod many;
 pub mod raw;
+pub mod rpc;
 pub mod tauri_events;
 
+pub use adapters::EventStreamExt;
+pub use many::{listen_many, listen_pattern, ListenMany, ListenPattern};
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Event<T> {
     pub event: String,
@@ -35,22 +41,29 @@ where
 
 pub async fn emit<P: Serialize>(event: &str, payload: &P) -> Result<(), crate::Error> {
     let value = serde_wasm_bindgen::to_value(payload)?;
-    raw::emit(event, &value).await?;
+    if !crate::session::emit(event, &value)? {
+        raw::emit(event, &value).await?;
+    }
     Ok(())
 }
 
 /// An event listener stream
+///
+/// When a [session](crate::session) is being replayed, it receives the recorded events instead.
 pub struct Listen<T> {
     receiver: mpsc::UnboundedReceiver<Event<T>>,
-    unlisten: js_sys::Function,
-    _closure: Closure<dyn FnMut(JsValue)>,
+    unlisten: Option<js_sys::Function>,
+    _closure: Option<Closure<dyn FnMut(JsValue)>>,
 }
 
 impl<T> Drop for Listen<T> {
     fn drop(&mut self) {
+        let Some(unlisten) = self.unlisten.take() else {
+            return;
+        };
         #[cfg(feature = "log")]
         log::trace!("Calling unlisten for event listener stream");
-        let _res = self.unlisten.call0(&JsValue::undefined());
+        let _res = unlisten.call0(&JsValue::undefined());
         #[cfg(feature = "log")]
         {
             match _res {
@@ -71,46 +84,65 @@ impl<T> Stream for Listen<T> {
     }
 }
 
+/// Send the raw `event` to `tx`, returns `false` if the receiver was dropped.
+fn send_event<T: DeserializeOwned>(tx: &mpsc::UnboundedSender<Event<T>>, event: JsValue) -> bool {
+    match serde_wasm_bindgen::from_value::<Event<T>>(event) {
+        Ok(value) => {
+            let res = tx.unbounded_send(value);
+            #[cfg(feature = "log")]
+            if let Err(err) = &res {
+                log::error!("{err}");
+            }
+            res.is_ok()
+        }
+        Err(_err) => {
+            #[cfg(feature = "log")]
+            log::error!("{_err}");
+            true
+        }
+    }
+}
+
 pub async fn listen<T>(event: &str) -> crate::Result<Listen<T>>
 where
     T: DeserializeOwned + 'static,
 {
     let (tx, rx) = mpsc::unbounded::<Event<T>>();
+    if crate::session::is_replaying() {
+        crate::session::replay_listen(event, move |event| send_event(&tx, event));
+        return Ok(Listen {
+            receiver: rx,
+            unlisten: None,
+            _closure: None,
+        });
+    }
     let handler = Closure::new(move |event: JsValue| {
-        match serde_wasm_bindgen::from_value::<Event<T>>(event) {
-            Ok(value) => {
-                let _res = tx.unbounded_send(value);
-                #[cfg(feature = "log")]
-                if let Err(err) = _res {
-                    log::error!("{err}");
-                }
-            }
-            Err(_err) => {
-                #[cfg(feature = "log")]
-                log::error!("{_err}");
-            }
-        }
+        crate::session::received(&event);
+        send_event(&tx, event);
     });
     let unlisten = raw::listen(event, &handler).await?;
 
     Ok(Listen {
         receiver: rx,
-        unlisten,
-        _closure: handler,
+        unlisten: Some(unlisten),
+        _closure: Some(handler),
     })
 }
 
 pub struct Once<T> {
     rx: oneshot::Receiver<Event<T>>,
-    unlisten: js_sys::Function,
-    _closure: Closure<dyn FnMut(JsValue)>,
+    unlisten: Option<js_sys::Function>,
+    _closure: Option<Closure<dyn FnMut(JsValue)>>,
 }
 
 impl<T> Drop for Once<T> {
     fn drop(&mut self) {
+        let Some(unlisten) = self.unlisten.take() else {
+            return;
+        };
         #[cfg(feature = "log")]
         log::trace!("Calling unlisten for once event");
-        let _res = self.unlisten.call0(&JsValue::undefined());
+        let _res = unlisten.call0(&JsValue::undefined());
         #[cfg(feature = "log")]
         {
             match _res {
@@ -137,26 +169,42 @@ where
 {
     pub async fn new(event: &str) -> crate::Result<Self> {
         let (tx, rx) = oneshot::channel::<Event<T>>();
-        let handler = Closure::once(move |event: JsValue| {
-            match serde_wasm_bindgen::from_value::<Event<T>>(event) {
-                Ok(value) => {
-                    let _res = tx.send(value);
-                    #[cfg(feature = "log")]
-                    if _res.is_err() {
-                        log::error!("Droped Receiver");
-                    }
-                }
-                Err(_err) => {
-                    #[cfg(feature = "log")]
-                    log::error!("{_err}");
+        let send = move |event: JsValue| match serde_wasm_bindgen::from_value::<Event<T>>(event) {
+            Ok(value) => {
+                let _res = tx.send(value);
+                #[cfg(feature = "log")]
+                if _res.is_err() {
+                    log::error!("Droped Receiver");
                 }
             }
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                log::error!("{_err}");
+            }
+        };
+        if crate::session::is_replaying() {
+            let mut send = Some(send);
+            crate::session::replay_listen(event, move |event| {
+                if let Some(send) = send.take() {
+                    send(event);
+                }
+                false
+            });
+            return Ok(Self {
+                rx,
+                unlisten: None,
+                _closure: None,
+            });
+        }
+        let handler = Closure::once(move |event: JsValue| {
+            crate::session::received(&event);
+            send(event);
         });
         let unlisten = raw::once(event, &handler).await?;
         Ok(Self {
             rx,
-            unlisten,
-            _closure: handler,
+            unlisten: Some(unlisten),
+            _closure: Some(handler),
         })
     }
 }
@@ -0,0 +1,182 @@
+//! Rate limiting adapters for high-frequency event streams.
+//!
+//! Events like `tauri://resize`, `tauri://move` or `tauri://scale-change` fire many times per second
+//! while the user drags a window.
+//! These adapters reduce them with browser timers and `requestAnimationFrame`, so they don't need any async runtime.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use futures::StreamExt;
+//! use tauri_global_sys::event::{listen, tauri_events, EventStreamExt};
+//!
+//! # async fn run() -> Result<(), tauri_global_sys::Error> {
+//! let mut resized = listen::<serde_json::Value>(&tauri_events::window_resized())
+//!     .await?
+//!     .debounce(Duration::from_millis(150));
+//! while let Some(event) = resized.next().await {
+//!     // relayout with `event.payload`
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    stream::{Fuse, Stream},
+    FutureExt, StreamExt,
+};
+
+use crate::timer::{AnimationFrame, Sleep};
+
+/// Stream for the [`debounce`](EventStreamExt::debounce) method.
+pub struct Debounce<S: Stream> {
+    stream: Fuse<S>,
+    duration: Duration,
+    pending: Option<S::Item>,
+    sleep: Option<Sleep>,
+}
+
+// The buffered item is never pinned.
+impl<S: Stream + Unpin> Unpin for Debounce<S> {}
+
+impl<S: Stream + Unpin> Stream for Debounce<S> {
+    type Item = S::Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while let Poll::Ready(Some(item)) = this.stream.poll_next_unpin(cx) {
+            this.pending = Some(item);
+            this.sleep = Some(Sleep::new(this.duration));
+        }
+        if this.stream.is_done() {
+            this.sleep = None;
+            return Poll::Ready(this.pending.take());
+        }
+        if let Some(sleep) = this.sleep.as_mut() {
+            if sleep.poll_unpin(cx).is_ready() {
+                this.sleep = None;
+                if let Some(item) = this.pending.take() {
+                    return Poll::Ready(Some(item));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Stream for the [`throttle`](EventStreamExt::throttle) method.
+pub struct Throttle<S: Stream> {
+    stream: Fuse<S>,
+    duration: Duration,
+    pending: Option<S::Item>,
+    cooldown: Option<Sleep>,
+}
+
+// The buffered item is never pinned.
+impl<S: Stream + Unpin> Unpin for Throttle<S> {}
+
+impl<S: Stream + Unpin> Stream for Throttle<S> {
+    type Item = S::Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while let Poll::Ready(Some(item)) = this.stream.poll_next_unpin(cx) {
+            if this.cooldown.is_none() {
+                this.cooldown = Some(Sleep::new(this.duration));
+                return Poll::Ready(Some(item));
+            }
+            this.pending = Some(item);
+        }
+        if this.stream.is_done() {
+            this.cooldown = None;
+            return Poll::Ready(this.pending.take());
+        }
+        if let Some(cooldown) = this.cooldown.as_mut() {
+            if cooldown.poll_unpin(cx).is_ready() {
+                this.cooldown = None;
+                if let Some(item) = this.pending.take() {
+                    this.cooldown = Some(Sleep::new(this.duration));
+                    return Poll::Ready(Some(item));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Stream for the [`latest_per_animation_frame`](EventStreamExt::latest_per_animation_frame) method.
+pub struct LatestPerAnimationFrame<S: Stream> {
+    stream: Fuse<S>,
+    pending: Option<S::Item>,
+    frame: Option<AnimationFrame>,
+}
+
+// The buffered item is never pinned.
+impl<S: Stream + Unpin> Unpin for LatestPerAnimationFrame<S> {}
+
+impl<S: Stream + Unpin> Stream for LatestPerAnimationFrame<S> {
+    type Item = S::Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while let Poll::Ready(Some(item)) = this.stream.poll_next_unpin(cx) {
+            this.pending = Some(item);
+            if this.frame.is_none() {
+                this.frame = Some(AnimationFrame::new());
+            }
+        }
+        if this.stream.is_done() {
+            this.frame = None;
+            return Poll::Ready(this.pending.take());
+        }
+        if let Some(frame) = this.frame.as_mut() {
+            if frame.poll_unpin(cx).is_ready() {
+                this.frame = None;
+                if let Some(item) = this.pending.take() {
+                    return Poll::Ready(Some(item));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Rate limiting adapters for event streams like [`Listen`](super::Listen).
+pub trait EventStreamExt: Stream + Unpin + Sized {
+    /// Only yield an item once no other item came for `duration`.
+    ///
+    /// The yielded item is the last one received.
+    fn debounce(self, duration: Duration) -> Debounce<Self> {
+        Debounce {
+            stream: self.fuse(),
+            duration,
+            pending: None,
+            sleep: None,
+        }
+    }
+
+    /// Yield at most one item per `duration`.
+    ///
+    /// The first item is yielded right away.
+    /// The latest item received during the cooldown is yielded when it ends.
+    fn throttle(self, duration: Duration) -> Throttle<Self> {
+        Throttle {
+            stream: self.fuse(),
+            duration,
+            pending: None,
+            cooldown: None,
+        }
+    }
+
+    /// Yield at most one item per animation frame, the latest one received.
+    fn latest_per_animation_frame(self) -> LatestPerAnimationFrame<Self> {
+        LatestPerAnimationFrame {
+            stream: self.fuse(),
+            pending: None,
+            frame: None,
+        }
+    }
+}
+
+impl<S: Stream + Unpin> EventStreamExt for S {}
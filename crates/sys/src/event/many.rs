@@ -0,0 +1,229 @@
+//! Listen to several events with a single stream.
+//!
+//! [`listen_many`] merges a fixed set of event names.
+//! [`listen_pattern`] subscribes to the event names matching an [`EventPattern`] as they get [announced](announce),
+//! since Tauri v1 has no wildcard listener.
+//!
+//! In both cases, every underlying listener is removed when the stream is dropped.
+use std::{
+    collections::HashSet,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{
+    future::{try_join_all, LocalBoxFuture},
+    stream::{FuturesUnordered, SelectAll},
+    FutureExt, Stream, StreamExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{emit, listen, Event, Listen};
+
+/// The event on which dynamically created event names are announced.
+///
+/// Its payload is the announced event name.
+pub const ANNOUNCE_EVENT: &str = "tauri-global-sys://announce";
+
+/// A stream merging several [`Listen`] streams.
+///
+/// The name of the event is available in [`Event::event`].
+pub struct ListenMany<T> {
+    events: SelectAll<Listen<T>>,
+}
+
+impl<T> ListenMany<T> {
+    /// The number of events listened to.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl<T> Stream for ListenMany<T> {
+    type Item = Event<T>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_next_unpin(cx)
+    }
+}
+
+/// Listen to all the given `events` at once.
+pub async fn listen_many<T>(events: &[&str]) -> crate::Result<ListenMany<T>>
+where
+    T: DeserializeOwned + 'static,
+{
+    let listeners = try_join_all(events.iter().map(|event| listen::<T>(event))).await?;
+    Ok(ListenMany {
+        events: listeners.into_iter().collect(),
+    })
+}
+
+/// A pattern matching event names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EventPattern {
+    /// Matches only this name.
+    Exact(String),
+    /// Matches every name starting with this prefix.
+    Prefix(String),
+}
+
+impl EventPattern {
+    /// Parse a pattern, a trailing `*` makes it a [prefix](EventPattern::Prefix) pattern.
+    ///
+    /// `sync://*` matches every event starting with `sync://`.
+    pub fn new(pattern: &str) -> Self {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => Self::Prefix(prefix.to_string()),
+            None => Self::Exact(pattern.to_string()),
+        }
+    }
+
+    pub fn matches(&self, event: &str) -> bool {
+        match self {
+            Self::Exact(name) => name == event,
+            Self::Prefix(prefix) => event.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+impl From<&str> for EventPattern {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Announce that events will be emitted with the name `event`,
+/// so the [`ListenPattern`] streams matching it subscribe to it.
+///
+/// The subscription is asynchronous, events emitted right after the announcement might be missed.
+pub async fn announce(event: &str) -> crate::Result<()> {
+    emit(ANNOUNCE_EVENT, &event).await
+}
+
+/// [Announce](announce) `event` and then emit it.
+pub async fn emit_announced<P: Serialize>(event: &str, payload: &P) -> crate::Result<()> {
+    announce(event).await?;
+    emit(event, payload).await
+}
+
+type Subscription<T> = LocalBoxFuture<'static, (String, crate::Result<Listen<T>>)>;
+
+/// A stream of the events whose name matches an [`EventPattern`].
+///
+/// Created with [`listen_pattern`].
+pub struct ListenPattern<T> {
+    pattern: EventPattern,
+    announcements: Listen<String>,
+    subscribing: FuturesUnordered<Subscription<T>>,
+    subscribed: HashSet<String>,
+    events: SelectAll<Listen<T>>,
+}
+
+impl<T> ListenPattern<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    pub fn pattern(&self) -> &EventPattern {
+        &self.pattern
+    }
+
+    /// The names of the events currently listened to.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.subscribed.iter().map(String::as_str)
+    }
+
+    /// Subscribe to an already known event name.
+    ///
+    /// Returns `false` if the name doesn't match the pattern or is already subscribed.
+    pub fn subscribe(&mut self, event: &str) -> bool {
+        if !self.pattern.matches(event) || !self.subscribed.insert(event.to_string()) {
+            return false;
+        }
+        let event = event.to_string();
+        self.subscribing.push(
+            async move {
+                let listener = listen::<T>(&event).await;
+                (event, listener)
+            }
+            .boxed_local(),
+        );
+        true
+    }
+}
+
+impl<T> Stream for ListenPattern<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    type Item = Event<T>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        while let Poll::Ready(Some(announced)) = self.announcements.poll_next_unpin(cx) {
+            self.subscribe(&announced.payload);
+        }
+        while let Poll::Ready(Some((event, listener))) = self.subscribing.poll_next_unpin(cx) {
+            match listener {
+                Ok(listener) => self.events.push(listener),
+                Err(_err) => {
+                    #[cfg(feature = "log")]
+                    log::error!("Cannot listen to {event}: {_err}");
+                    // Allow a later announcement to retry.
+                    self.subscribed.remove(&event);
+                }
+            }
+        }
+        if self.events.is_empty() {
+            return Poll::Pending;
+        }
+        match self.events.poll_next_unpin(cx) {
+            Poll::Ready(Some(event)) => Poll::Ready(Some(event)),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+/// Listen to every event whose name matches `pattern`,
+/// starting with the names in `known` and then with the ones [announced](announce) later.
+pub async fn listen_pattern<T>(
+    pattern: impl Into<EventPattern>,
+    known: &[&str],
+) -> crate::Result<ListenPattern<T>>
+where
+    T: DeserializeOwned + 'static,
+{
+    let mut listener = ListenPattern {
+        pattern: pattern.into(),
+        announcements: listen::<String>(ANNOUNCE_EVENT).await?,
+        subscribing: FuturesUnordered::new(),
+        subscribed: HashSet::new(),
+        events: SelectAll::new(),
+    };
+    for event in known {
+        listener.subscribe(event);
+    }
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventPattern;
+
+    #[test]
+    fn pattern_exact() {
+        let pattern = EventPattern::new("sync://users");
+        assert_eq!(pattern, EventPattern::Exact("sync://users".into()));
+        assert!(pattern.matches("sync://users"));
+        assert!(!pattern.matches("sync://users/1"));
+    }
+
+    #[test]
+    fn pattern_prefix() {
+        let pattern = EventPattern::new("sync://*");
+        assert_eq!(pattern, EventPattern::Prefix("sync://".into()));
+        assert!(pattern.matches("sync://users"));
+        assert!(pattern.matches("sync://"));
+        assert!(!pattern.matches("async://users"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Request/response calls between windows built on top of the event system.
+//!
+//! A request is emitted on a channel event and tagged with a unique id.
+//! The reply is sent back on an event derived from the channel and that id (see [`response_event`]),
+//! so only the window that made the request receives it.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use tauri_global_sys::event::rpc;
+//!
+//! # async fn run() -> Result<(), tauri_global_sys::Error> {
+//! // In the window serving the requests
+//! let _responder = rpc::serve("math/add", |(a, b): (i32, i32)| async move {
+//!     Ok::<_, String>(a + b)
+//! })
+//! .await?;
+//!
+//! // In any other window
+//! let sum: i32 = rpc::request("math/add", &(1, 2), Duration::from_secs(5)).await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::{fmt::Display, future::Future, time::Duration};
+
+use futures::{
+    future::{AbortHandle, Abortable},
+    FutureExt, StreamExt,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+
+use super::{emit, listen, Once};
+
+/// The payload emitted on the channel event by [`request`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RpcRequest<T> {
+    pub id: String,
+    pub payload: T,
+}
+
+/// The payload emitted on the [response event](response_event) by a [`Responder`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RpcResponse<T> {
+    pub id: String,
+    pub result: Result<T, String>,
+}
+
+/// The name of the event on which the response to the request `id` is sent.
+pub fn response_event(channel: &str, id: &str) -> String {
+    format!("{channel}/response/{id}")
+}
+
+/// Send a request on `channel` and wait for its response.
+///
+/// Fails with [`Error::Timeout`](crate::Error::Timeout) if no response came within `timeout`
+/// and with [`Error::EventRpc`](crate::Error::EventRpc) if the responder returned an error.
+pub async fn request<Req, Resp>(channel: &str, req: &Req, timeout: Duration) -> crate::Result<Resp>
+where
+    Req: Serialize,
+    Resp: DeserializeOwned + 'static,
+{
+    let id = Uuid::new_v4().to_string();
+    // Listen before emitting so that a fast responder can't reply before we are ready.
+    let reply = Once::<RpcResponse<Resp>>::new(&response_event(channel, &id)).await?;
+    emit(
+        channel,
+        &RpcRequest {
+            id: id.clone(),
+            payload: req,
+        },
+    )
+    .await?;
+    let event = crate::timer::timeout(timeout, reply).await??;
+    event.payload.result.map_err(crate::Error::EventRpc)
+}
+
+/// A handle to a running request handler started with [`serve`].
+///
+/// The handler stops serving requests (and its event listener is removed) when this is dropped.
+#[derive(Debug)]
+pub struct Responder {
+    handle: AbortHandle,
+}
+
+impl Responder {
+    /// Stop serving requests.
+    pub fn stop(self) {}
+}
+
+impl Drop for Responder {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Serve the requests sent on `channel` with `handler`.
+///
+/// Each request is handled in its own local task, so a slow request doesn't block the others.
+pub async fn serve<Req, Resp, E, F, Fut>(channel: &str, mut handler: F) -> crate::Result<Responder>
+where
+    Req: DeserializeOwned + 'static,
+    Resp: Serialize + 'static,
+    E: Display,
+    F: FnMut(Req) -> Fut + 'static,
+    Fut: Future<Output = Result<Resp, E>> + 'static,
+{
+    let mut requests = listen::<RpcRequest<Req>>(channel).await?;
+    let channel = channel.to_string();
+    let (handle, registration) = AbortHandle::new_pair();
+    let task = async move {
+        while let Some(event) = requests.next().await {
+            let RpcRequest { id, payload } = event.payload;
+            let event_name = response_event(&channel, &id);
+            let reply = handler(payload);
+            spawn_local(async move {
+                let result = reply.await.map_err(|err| err.to_string());
+                let _res = emit(&event_name, &RpcResponse { id, result }).await;
+                #[cfg(feature = "log")]
+                if let Err(err) = _res {
+                    log::error!("Cannot send the response on {event_name}: {err}");
+                }
+            });
+        }
+    };
+    spawn_local(Abortable::new(task, registration).map(|_| ()));
+    Ok(Responder { handle })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{response_event, RpcResponse};
+
+    #[test]
+    fn response_event_name() {
+        assert_eq!(response_event("math/add", "1234"), "math/add/response/1234");
+    }
+
+    #[test]
+    fn response_ser() {
+        let ok = RpcResponse::<u8> {
+            id: "a".into(),
+            result: Ok(3),
+        };
+        let err = RpcResponse::<u8> {
+            id: "b".into(),
+            result: Err("nope".into()),
+        };
+        assert_eq!(
+            serde_json::to_value(ok).unwrap(),
+            json!({ "id": "a", "result": { "Ok": 3 } })
+        );
+        assert_eq!(
+            serde_json::to_value(err).unwrap(),
+            json!({ "id": "b", "result": { "Err": "nope" } })
+        );
+    }
+}
@@ -51,12 +51,55 @@
 //! Trying to execute any API with a URL not configured on the scope results in a promise rejection due to denied access.
 //!
 //! Note that this scope applies to all APIs on this module.
+//! The rejection can be predicted locally with [`FsScope`](scope::FsScope).
+use std::{cell::RefCell, collections::HashMap};
+
+use bytes::Bytes;
 use enum_all_variants::AllVariants;
 use enum_repr::EnumRepr;
+use js_sys::Uint8Array;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::session;
 
+#[cfg(feature = "zip")]
+pub mod archive;
+pub mod atomic;
+pub mod copy;
+pub mod dir;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+#[cfg(feature = "crypto")]
+pub mod encrypted;
+pub mod glob;
+pub mod json;
+#[cfg(feature = "log")]
+pub mod logger;
+pub mod memory;
 pub mod raw;
+pub mod scope;
+pub mod store;
+pub mod system;
+pub mod temp;
+pub mod walk;
+pub mod watch;
+
+pub use atomic::{write_binary_atomic, write_text_atomic, AtomicWriteOptions};
+pub use copy::{copy_dir, move_dir, CopyDirOptions, CopyReport, OverwritePolicy};
+pub use dir::Dir;
+#[cfg(feature = "encoding")]
+pub use encoding::{read_text_file_with_encoding, write_text_file_with_encoding};
+pub use glob::GlobPattern;
+pub use json::JsonFile;
+pub use memory::InMemoryFs;
+pub use scope::FsScope;
+pub use store::KvStore;
+pub use system::{FileSystem, TauriFs};
+pub use temp::{TempDir, TempFile};
+pub use walk::{walk_dir, EntryFilter, WalkDir, WalkDirOptions, WalkEntry};
+pub use watch::{watch, Watch, WatchEvent};
 
 #[EnumRepr(type = "u8", implicit = true)]
 #[derive(
@@ -114,10 +157,12 @@ pub struct FileEntry {
 /// Struct interface to [`FsBinaryFileOption`](https://v1.tauri.app/v1/api/js/fs#fsbinaryfileoption)
 ///
 /// Options object used to write a binary data to a file.
+///
+/// The contents are serialized as a single `Uint8Array`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct FsBinaryFileOption {
-    pub contents: Vec<u8>,
+    pub contents: Bytes,
     pub path: String,
 }
 
@@ -154,6 +199,96 @@ pub struct FsTextFileOption {
     pub path: String,
 }
 
+impl BaseDirectory {
+    /// The variable standing for this directory in the fs scope patterns, like `$APPDATA`.
+    pub fn variable(&self) -> &'static str {
+        match self {
+            Self::Audio => "$AUDIO",
+            Self::Cache => "$CACHE",
+            Self::Config => "$CONFIG",
+            Self::Data => "$DATA",
+            Self::LocalData => "$LOCALDATA",
+            Self::Desktop => "$DESKTOP",
+            Self::Document => "$DOCUMENT",
+            Self::Download => "$DOWNLOAD",
+            Self::Executable => "$EXE",
+            Self::Font => "$FONT",
+            Self::Home => "$HOME",
+            Self::Picture => "$PICTURE",
+            Self::Public => "$PUBLIC",
+            Self::Runtime => "$RUNTIME",
+            Self::Template => "$TEMPLATE",
+            Self::Video => "$VIDEO",
+            Self::Resource => "$RESOURCE",
+            Self::App => "$APP",
+            Self::Log => "$LOG",
+            Self::Temp => "$TEMP",
+            Self::AppConfig => "$APPCONFIG",
+            Self::AppData => "$APPDATA",
+            Self::AppLocalData => "$APPLOCALDATA",
+            Self::AppCache => "$APPCACHE",
+            Self::AppLog => "$APPLOG",
+        }
+    }
+
+    /// The directory whose [`variable`](Self::variable) is `variable`, like `$APPDATA`.
+    pub fn from_variable(variable: &str) -> Option<Self> {
+        Self::all_variants()
+            .iter()
+            .copied()
+            .find(|base_dir| base_dir.variable() == variable)
+    }
+
+    /// The absolute path of the directory, given by the matching `path` function
+    /// (or [`os::tempdir`](crate::os::tempdir) for [`BaseDirectory::Temp`]).
+    ///
+    /// The directories are memoized, they only change with the app identifier.
+    pub async fn resolve(&self) -> crate::Result<String> {
+        if let Some(resolved) = RESOLVED.with_borrow(|resolved| resolved.get(self).cloned()) {
+            return Ok(resolved);
+        }
+        use crate::path;
+        let resolved = match self {
+            Self::Audio => path::audio_dir().await,
+            Self::Cache => path::cache_dir().await,
+            Self::Config => path::config_dir().await,
+            Self::Data => path::data_dir().await,
+            Self::LocalData => path::local_data_dir().await,
+            Self::Desktop => path::desktop_dir().await,
+            Self::Document => path::document_dir().await,
+            Self::Download => path::download_dir().await,
+            Self::Executable => path::executable_dir().await,
+            Self::Font => path::font_dir().await,
+            Self::Home => path::home_dir().await,
+            Self::Picture => path::picture_dir().await,
+            Self::Public => path::public_dir().await,
+            Self::Runtime => path::runtime_dir().await,
+            Self::Template => path::template_dir().await,
+            Self::Video => path::video_dir().await,
+            Self::Resource => path::resource_dir().await,
+            Self::App => path::app_dir().await,
+            Self::Log => path::log_dir().await,
+            Self::Temp => crate::os::tempdir().await,
+            Self::AppConfig => path::app_config_dir().await,
+            Self::AppData => path::app_data_dir().await,
+            Self::AppLocalData => path::app_local_dir().await,
+            Self::AppCache => path::app_cache_dir().await,
+            Self::AppLog => path::app_log_dir().await,
+        }?;
+        RESOLVED.with_borrow_mut(|cache| cache.insert(*self, resolved.clone()));
+        Ok(resolved)
+    }
+}
+
+thread_local! {
+    /// The cache of [`BaseDirectory::resolve`].
+    static RESOLVED: RefCell<HashMap<BaseDirectory, String>> = RefCell::new(HashMap::new());
+}
+
+fn unit(_: ()) -> JsValue {
+    JsValue::UNDEFINED
+}
+
 /// Copies a file to a destination.
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#copyfile>
@@ -162,7 +297,23 @@ pub async fn copy_file(
     destination: &str,
     options: Option<FsOptions>,
 ) -> crate::Result<()> {
-    raw::copyFile(source, destination, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(source, options.as_ref().and_then(|options| options.dir))?;
+    scope::pre_check(
+        destination,
+        options.as_ref().and_then(|options| options.dir),
+    )?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call(
+        "fs",
+        "copyFile",
+        &[&source.into(), &destination.into(), &options],
+        async {
+            raw::copyFile(source, destination, options.clone())
+                .await
+                .map(unit)
+        },
+    )
+    .await?;
     Ok(())
 }
 
@@ -172,7 +323,12 @@ pub async fn copy_file(
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#createdir>
 pub async fn create_dir(dir: &str, options: Option<FsDirOptions>) -> crate::Result<()> {
-    raw::createDir(dir, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(dir, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call("fs", "createDir", &[&dir.into(), &options], async {
+        raw::createDir(dir, options.clone()).await.map(unit)
+    })
+    .await?;
     Ok(())
 }
 
@@ -182,33 +338,70 @@ pub async fn create_dir(dir: &str, options: Option<FsDirOptions>) -> crate::Resu
 ///
 /// Since: 1.1.0
 pub async fn exists(path: &str, options: Option<FsOptions>) -> crate::Result<bool> {
-    Ok(serde_wasm_bindgen::from_value(
-        raw::exists(path, serde_wasm_bindgen::to_value(&options)?).await?,
-    )?)
+    scope::pre_check(path, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("fs", "exists", &[&path.into(), &options], async {
+        raw::exists(path, options.clone()).await
+    })
+    .await?;
+    Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
 /// Reads a file as byte array.
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#readbinaryfile>
 pub async fn read_binary_file(path: &str, options: Option<FsOptions>) -> crate::Result<Vec<u8>> {
-    let res = raw::readBinaryFile(path, serde_wasm_bindgen::to_value(&options)?).await?;
-    Ok(res.to_vec())
+    Ok(read_binary_file_raw(path, options).await?.to_vec())
+}
+
+/// Same as [`read_binary_file`] but returns the JS `Uint8Array` as is,
+/// without copying it into the WebAssembly memory.
+///
+/// Ref: <https://v1.tauri.app/v1/api/js/fs#readbinaryfile>
+pub async fn read_binary_file_raw(
+    path: &str,
+    options: Option<FsOptions>,
+) -> crate::Result<Uint8Array> {
+    scope::pre_check(path, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("fs", "readBinaryFile", &[&path.into(), &options], async {
+        raw::readBinaryFile(path, options.clone())
+            .await
+            .map(JsValue::from)
+    })
+    .await?;
+    // A replayed session gives back an array of numbers.
+    Ok(res
+        .dyn_into::<Uint8Array>()
+        .unwrap_or_else(|res| Uint8Array::new(&res)))
 }
 
 /// List directory files.
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#readdir>
 pub async fn read_dir(dir: &str, options: Option<FsDirOptions>) -> crate::Result<Vec<FileEntry>> {
-    Ok(serde_wasm_bindgen::from_value(
-        raw::readDir(dir, serde_wasm_bindgen::to_value(&options)?).await?,
-    )?)
+    scope::pre_check(dir, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call("fs", "readDir", &[&dir.into(), &options], async {
+        raw::readDir(dir, options.clone()).await
+    })
+    .await?;
+    Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
 /// Reads a file as an UTF-8 encoded string.
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#readtextfile>
 pub async fn read_text_file(file_path: &str, options: Option<FsOptions>) -> crate::Result<String> {
-    let res = raw::readTextFile(file_path, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(file_path, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = session::module_call(
+        "fs",
+        "readTextFile",
+        &[&file_path.into(), &options],
+        async { raw::readTextFile(file_path, options.clone()).await },
+    )
+    .await?;
     Ok(serde_wasm_bindgen::from_value(res)?)
 }
 
@@ -218,7 +411,12 @@ pub async fn read_text_file(file_path: &str, options: Option<FsOptions>) -> crat
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#removedir>
 pub async fn remove_dir(dir: &str, options: Option<FsDirOptions>) -> crate::Result<()> {
-    raw::removeDir(dir, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(dir, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call("fs", "removeDir", &[&dir.into(), &options], async {
+        raw::removeDir(dir, options.clone()).await.map(unit)
+    })
+    .await?;
     Ok(())
 }
 
@@ -226,7 +424,12 @@ pub async fn remove_dir(dir: &str, options: Option<FsDirOptions>) -> crate::Resu
 ///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#removefile>
 pub async fn remove_file(file: &str, options: Option<FsOptions>) -> crate::Result<()> {
-    raw::removeFile(file, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(file, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call("fs", "removeFile", &[&file.into(), &options], async {
+        raw::removeFile(file, options.clone()).await.map(unit)
+    })
+    .await?;
     Ok(())
 }
 
@@ -238,22 +441,45 @@ pub async fn rename_file(
     new_path: &str,
     options: Option<FsOptions>,
 ) -> crate::Result<()> {
-    raw::renameFile(old_path, new_path, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(old_path, options.as_ref().and_then(|options| options.dir))?;
+    scope::pre_check(new_path, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call(
+        "fs",
+        "renameFile",
+        &[&old_path.into(), &new_path.into(), &options],
+        async {
+            raw::renameFile(old_path, new_path, options.clone())
+                .await
+                .map(unit)
+        },
+    )
+    .await?;
     Ok(())
 }
 
 /// Writes a byte array content to a file.
 ///
+/// The contents are copied once into a JS `Uint8Array`.
+///
 /// Ref: <https://v1.tauri.app/v1/api/js/fs#writebinaryfile>
 pub async fn write_binary_file(
     path: &str,
-    contents: Vec<u8>,
+    contents: impl AsRef<[u8]>,
     options: Option<FsOptions>,
 ) -> crate::Result<()> {
-    raw::writeBinaryFile(
-        path,
-        serde_wasm_bindgen::to_value(&contents)?,
-        serde_wasm_bindgen::to_value(&options)?,
+    scope::pre_check(path, options.as_ref().and_then(|options| options.dir))?;
+    let contents = JsValue::from(Uint8Array::from(contents.as_ref()));
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call(
+        "fs",
+        "writeBinaryFile",
+        &[&path.into(), &contents, &options],
+        async {
+            raw::writeBinaryFile(path, contents.clone(), options.clone())
+                .await
+                .map(unit)
+        },
     )
     .await?;
     Ok(())
@@ -266,10 +492,14 @@ pub async fn write_binary_file2(
     file: FsBinaryFileOption,
     options: Option<FsOptions>,
 ) -> crate::Result<()> {
-    raw::writeBinaryFile2(
-        serde_wasm_bindgen::to_value(&file)?,
-        serde_wasm_bindgen::to_value(&options)?,
-    )
+    scope::pre_check(&file.path, options.as_ref().and_then(|options| options.dir))?;
+    let file = serde_wasm_bindgen::to_value(&file)?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call("fs", "writeBinaryFile", &[&file, &options], async {
+        raw::writeBinaryFile2(file.clone(), options.clone())
+            .await
+            .map(unit)
+    })
     .await?;
     Ok(())
 }
@@ -282,7 +512,19 @@ pub async fn write_text_file(
     contents: &str,
     options: Option<FsOptions>,
 ) -> crate::Result<()> {
-    raw::writeTextFile(path, contents, serde_wasm_bindgen::to_value(&options)?).await?;
+    scope::pre_check(path, options.as_ref().and_then(|options| options.dir))?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call(
+        "fs",
+        "writeTextFile",
+        &[&path.into(), &contents.into(), &options],
+        async {
+            raw::writeTextFile(path, contents, options.clone())
+                .await
+                .map(unit)
+        },
+    )
+    .await?;
     Ok(())
 }
 
@@ -293,10 +535,14 @@ pub async fn write_text_file2(
     file: FsTextFileOption,
     options: Option<FsOptions>,
 ) -> crate::Result<()> {
-    raw::writeTextFile2(
-        serde_wasm_bindgen::to_value(&file)?,
-        serde_wasm_bindgen::to_value(&options)?,
-    )
+    scope::pre_check(&file.path, options.as_ref().and_then(|options| options.dir))?;
+    let file = serde_wasm_bindgen::to_value(&file)?;
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    session::module_call("fs", "writeTextFile", &[&file, &options], async {
+        raw::writeTextFile2(file.clone(), options.clone())
+            .await
+            .map(unit)
+    })
     .await?;
     Ok(())
 }
@@ -305,7 +551,7 @@ pub async fn write_text_file2(
 mod tests {
     use serde_json::{to_value, Value};
 
-    use crate::fs::BaseDirectory;
+    use crate::fs::{BaseDirectory, FsBinaryFileOption};
     #[test]
     fn base_dir_ser() {
         assert_eq!(
@@ -313,4 +559,10 @@ mod tests {
             Value::Number((BaseDirectory::Audio as u8).into())
         )
     }
+    #[test]
+    fn binary_file_option_from_numbers() {
+        let option: FsBinaryFileOption =
+            serde_json::from_str(r#"{"contents":[1,2,255],"path":"a.bin"}"#).unwrap();
+        assert_eq!(option.contents.as_ref(), &[1, 2, 255]);
+    }
 }
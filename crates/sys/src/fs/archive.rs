@@ -0,0 +1,356 @@
+//! Zip archives read and written in pure Rust.
+//!
+//! Built on [`read_binary_file`](super::read_binary_file), [`write_binary_file`](super::write_binary_file)
+//! and [`create_dir`](super::create_dir): the archives are handled in memory. Requires the `zip` feature.
+use std::{
+    collections::BTreeSet,
+    io::{Cursor, Read, Write},
+};
+
+use bytes::Bytes;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{walk::join, BaseDirectory, FsDirOptions, FsOptions};
+
+/// The default maximum total size of the extracted files, see [`extract_zip_with_limit`].
+pub const DEFAULT_MAX_EXTRACTED_SIZE: u64 = 1 << 30;
+
+/// The progress of an archive operation, sent after each entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArchiveProgress {
+    pub done: usize,
+    pub total: usize,
+    /// The name of the entry just handled, inside the archive.
+    pub name: String,
+}
+
+/// The contents of a [`ZipEntry`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ZipSource {
+    /// A file read with [`read_binary_file`](super::read_binary_file).
+    File {
+        path: String,
+        dir: Option<BaseDirectory>,
+    },
+    Bytes(Bytes),
+    /// An empty directory.
+    Dir,
+}
+
+/// An entry to add to an archive with [`create_zip`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ZipEntry {
+    /// The `/` separated path inside the archive.
+    pub name: String,
+    pub source: ZipSource,
+}
+
+impl ZipEntry {
+    pub fn file(
+        name: impl Into<String>,
+        path: impl Into<String>,
+        dir: Option<BaseDirectory>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            source: ZipSource::File {
+                path: path.into(),
+                dir,
+            },
+        }
+    }
+
+    pub fn bytes(name: impl Into<String>, contents: impl Into<Bytes>) -> Self {
+        Self {
+            name: name.into(),
+            source: ZipSource::Bytes(contents.into()),
+        }
+    }
+
+    pub fn dir(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: ZipSource::Dir,
+        }
+    }
+}
+
+/// Normalize an entry name to a relative `/` separated path,
+/// failing with [`Error::InvalidPath`](crate::Error::InvalidPath) if it escapes the destination (zip-slip).
+fn safe_path(name: &str) -> crate::Result<String> {
+    let invalid = |reason| crate::Error::InvalidPath {
+        path: name.into(),
+        reason,
+    };
+    let normalized = name.replace('\\', "/");
+    if normalized.starts_with('/') || normalized.as_bytes().get(1) == Some(&b':') {
+        return Err(invalid("absolute path in the archive"));
+    }
+    let mut components = Vec::new();
+    for component in normalized.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => return Err(invalid("parent directory in the archive")),
+            component => components.push(component),
+        }
+    }
+    if components.is_empty() {
+        return Err(invalid("empty path in the archive"));
+    }
+    Ok(components.join("/"))
+}
+
+/// The entries of an archive, [`None`] for the directories.
+///
+/// Fails with [`Error::ArchiveTooLarge`](crate::Error::ArchiveTooLarge) once the decompressed contents exceed `max_size`,
+/// whatever sizes the headers claim (zip bomb).
+fn unpack(bytes: &[u8], max_size: u64) -> crate::Result<Vec<(String, Option<Vec<u8>>)>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    let mut entries = Vec::new();
+    let mut remaining = max_size;
+    for index in 0..archive.len() {
+        let file = archive.by_index(index)?;
+        let path = safe_path(file.name())?;
+        if file.is_dir() {
+            entries.push((path, None));
+        } else {
+            let mut contents = Vec::new();
+            // One more byte than allowed tells an exceeding entry apart from one filling the limit exactly.
+            file.take(remaining.saturating_add(1))
+                .read_to_end(&mut contents)
+                .map_err(zip::result::ZipError::from)?;
+            remaining = remaining
+                .checked_sub(contents.len() as u64)
+                .ok_or(crate::Error::ArchiveTooLarge(max_size))?;
+            entries.push((path, Some(contents)));
+        }
+    }
+    Ok(entries)
+}
+
+fn pack(entries: Vec<(String, Option<Bytes>)>) -> crate::Result<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in entries {
+        match contents {
+            Some(contents) => {
+                writer.start_file(name, options)?;
+                writer
+                    .write_all(&contents)
+                    .map_err(zip::result::ZipError::from)?;
+            }
+            None => writer.add_directory(name, options)?,
+        }
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Extract the zip archive `src` into `dest_dir`, both relative to the `options` directory.
+///
+/// Returns the extracted paths, relative to `dest_dir`.
+pub async fn extract_zip(
+    src: &str,
+    dest_dir: &str,
+    options: Option<FsOptions>,
+) -> crate::Result<Vec<String>> {
+    extract_zip_with_progress(src, dest_dir, options, |_| {}).await
+}
+
+/// [`extract_zip`], calling `on_progress` after each entry.
+///
+/// Every entry is checked before anything is written.
+/// At most [`DEFAULT_MAX_EXTRACTED_SIZE`] bytes are extracted, see [`extract_zip_with_limit`].
+///
+/// ```rust,no_run
+/// use tauri_global_sys::fs::{archive::extract_zip_with_progress, BaseDirectory, FsOptions};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let options = FsOptions {
+///     dir: Some(BaseDirectory::AppData),
+///     ..Default::default()
+/// };
+/// extract_zip_with_progress("bundle.zip", "projects/bundle", Some(options), |progress| {
+///     println!("{}/{} {}", progress.done, progress.total, progress.name);
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn extract_zip_with_progress(
+    src: &str,
+    dest_dir: &str,
+    options: Option<FsOptions>,
+    on_progress: impl FnMut(&ArchiveProgress),
+) -> crate::Result<Vec<String>> {
+    extract_zip_with_limit(
+        src,
+        dest_dir,
+        options,
+        DEFAULT_MAX_EXTRACTED_SIZE,
+        on_progress,
+    )
+    .await
+}
+
+/// [`extract_zip_with_progress`], failing with [`Error::ArchiveTooLarge`](crate::Error::ArchiveTooLarge)
+/// if the extracted files would take more than `max_size` bytes.
+pub async fn extract_zip_with_limit(
+    src: &str,
+    dest_dir: &str,
+    options: Option<FsOptions>,
+    max_size: u64,
+    mut on_progress: impl FnMut(&ArchiveProgress),
+) -> crate::Result<Vec<String>> {
+    let bytes = super::read_binary_file(src, options.clone()).await?;
+    let entries = unpack(&bytes, max_size)?;
+    let dir = options.as_ref().and_then(|options| options.dir);
+    let dir_options = Some(FsDirOptions {
+        dir,
+        recursive: Some(true),
+    });
+    super::create_dir(dest_dir, dir_options.clone()).await?;
+    let mut created = BTreeSet::new();
+    let total = entries.len();
+    let mut extracted = Vec::with_capacity(total);
+    for (done, (name, contents)) in entries.into_iter().enumerate() {
+        let target = join(dest_dir, &name);
+        let parent = match contents {
+            None => Some(target.as_str()),
+            Some(_) => target.rsplit_once('/').map(|(parent, _)| parent),
+        };
+        if let Some(parent) = parent.filter(|parent| *parent != dest_dir) {
+            if created.insert(parent.to_string()) {
+                super::create_dir(parent, dir_options.clone()).await?;
+            }
+        }
+        if let Some(contents) = contents {
+            super::write_binary_file(
+                &target,
+                contents,
+                Some(FsOptions {
+                    dir,
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        }
+        on_progress(&ArchiveProgress {
+            done: done + 1,
+            total,
+            name: name.clone(),
+        });
+        extracted.push(name);
+    }
+    Ok(extracted)
+}
+
+/// Create the zip archive `dst`, relative to the `options` directory, from `entries`.
+pub async fn create_zip(
+    entries: impl IntoIterator<Item = ZipEntry>,
+    dst: &str,
+    options: Option<FsOptions>,
+) -> crate::Result<()> {
+    create_zip_with_progress(entries, dst, options, |_| {}).await
+}
+
+/// [`create_zip`], calling `on_progress` after each entry is read.
+pub async fn create_zip_with_progress(
+    entries: impl IntoIterator<Item = ZipEntry>,
+    dst: &str,
+    options: Option<FsOptions>,
+    mut on_progress: impl FnMut(&ArchiveProgress),
+) -> crate::Result<()> {
+    let entries = entries.into_iter().collect::<Vec<_>>();
+    let total = entries.len();
+    let mut packed = Vec::with_capacity(total);
+    for (done, entry) in entries.into_iter().enumerate() {
+        let name = safe_path(&entry.name)?;
+        let contents = match entry.source {
+            ZipSource::File { path, dir } => Some(
+                super::read_binary_file(
+                    &path,
+                    Some(FsOptions {
+                        dir,
+                        ..Default::default()
+                    }),
+                )
+                .await?
+                .into(),
+            ),
+            ZipSource::Bytes(bytes) => Some(bytes),
+            ZipSource::Dir => None,
+        };
+        on_progress(&ArchiveProgress {
+            done: done + 1,
+            total,
+            name: name.clone(),
+        });
+        packed.push((name, contents));
+    }
+    super::write_binary_file(dst, pack(packed)?, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{pack, safe_path, unpack};
+
+    #[test]
+    fn zip_slip() {
+        assert_eq!(safe_path("a/./b\\c.txt").unwrap(), "a/b/c.txt");
+        assert_eq!(safe_path("assets/").unwrap(), "assets");
+        for name in [
+            "../evil",
+            "a/../../evil",
+            "/etc/passwd",
+            "C:\\evil",
+            "",
+            "./",
+        ] {
+            assert!(
+                matches!(safe_path(name), Err(crate::Error::InvalidPath { .. })),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes = pack(vec![
+            ("assets".into(), None),
+            ("assets/a.txt".into(), Some(Bytes::from_static(b"hello"))),
+            ("b.bin".into(), Some(Bytes::from_static(&[0, 1, 2]))),
+        ])
+        .unwrap();
+        assert_eq!(
+            unpack(&bytes, u64::MAX).unwrap(),
+            vec![
+                ("assets".to_string(), None),
+                ("assets/a.txt".to_string(), Some(b"hello".to_vec())),
+                ("b.bin".to_string(), Some(vec![0, 1, 2])),
+            ]
+        );
+
+        let evil = pack(vec![("../evil".into(), Some(Bytes::new()))]).unwrap();
+        assert!(unpack(&evil, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn size_limit() {
+        let bytes = pack(vec![
+            ("a.bin".into(), Some(Bytes::from(vec![0; 600]))),
+            ("b.bin".into(), Some(Bytes::from(vec![0; 400]))),
+        ])
+        .unwrap();
+        assert_eq!(unpack(&bytes, 1000).unwrap().len(), 2);
+        assert!(matches!(
+            unpack(&bytes, 999),
+            Err(crate::Error::ArchiveTooLarge(999))
+        ));
+        assert!(matches!(
+            unpack(&bytes, 100),
+            Err(crate::Error::ArchiveTooLarge(100))
+        ));
+    }
+}
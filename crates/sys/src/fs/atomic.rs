@@ -0,0 +1,443 @@
+//! Atomic file writes.
+//!
+//! The contents are first written to a sibling temporary file (`<path>.<uuid>.tmp`) in the same [`BaseDirectory`],
+//! which is then renamed over the target. If the app is killed in the middle of a write,
+//! the target is left untouched and [`recover`] cleans up at the next startup.
+//!
+//! Every write has its own temporary file, so concurrent writes of the same file, from several windows for example,
+//! don't corrupt each other: the last rename wins.
+//!
+//! ```rust,no_run
+//! use tauri_global_sys::fs::{atomic, BaseDirectory};
+//!
+//! # async fn run() -> Result<(), tauri_global_sys::Error> {
+//! let options = atomic::AtomicWriteOptions {
+//!     dir: Some(BaseDirectory::AppConfig),
+//!     backup: true,
+//! };
+//! atomic::recover("config.json", options.clone()).await?;
+//! atomic::write_text_atomic("config.json", "{}", options).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The `_in` variants do the same through any [`FileSystem`], like an [`InMemoryFs`](super::InMemoryFs) in the tests.
+use uuid::Uuid;
+
+use super::{
+    system::{FileSystem, TauriFs},
+    BaseDirectory, FsDirOptions, FsOptions,
+};
+
+/// Options for the atomic writes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AtomicWriteOptions {
+    pub dir: Option<BaseDirectory>,
+    /// Keep the previous version of the file as `<path>.bak`.
+    pub backup: bool,
+}
+
+impl AtomicWriteOptions {
+    fn fs_options(&self) -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: self.dir,
+            ..Default::default()
+        })
+    }
+
+    fn fs_dir_options(&self) -> Option<FsDirOptions> {
+        Some(FsDirOptions {
+            dir: self.dir,
+            recursive: Some(false),
+        })
+    }
+}
+
+/// A new temporary file to write before swapping it in, unique to this write.
+pub fn temp_path(path: &str) -> String {
+    format!("{path}.{}.tmp", Uuid::new_v4().simple())
+}
+
+/// Whether the file named `name` is a temporary file of the file named `file`, see [`temp_path`].
+///
+/// The `<file>.tmp` files of the previous versions are matched too.
+fn is_temp_of(name: &str, file: &str) -> bool {
+    let Some(id) = name
+        .strip_prefix(file)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix("tmp"))
+    else {
+        return false;
+    };
+    id.is_empty()
+        || id
+            .strip_suffix('.')
+            .is_some_and(|id| id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// `path` split into its parent directory and its file name.
+fn split_parent(path: &str) -> (&str, &str) {
+    path.rsplit_once(['/', '\\']).unwrap_or(("", path))
+}
+
+/// The backup of the previous version of the file.
+pub fn backup_path(path: &str) -> String {
+    format!("{path}.bak")
+}
+
+/// Remove the `temp` file of a failed write.
+async fn discard<F>(fs: &F, temp: &str, options: &AtomicWriteOptions)
+where
+    F: FileSystem + ?Sized,
+{
+    let _re = fs.remove_file(temp, options.fs_options()).await;
+    #[cfg(feature = "log")]
+    if let Err(err) = _re {
+        // The failed step may not have created it.
+        if err.kind() != crate::ErrorKind::NotFound {
+            log::error!("Error on removing the temporary file {temp} ({err:?})");
+        }
+    }
+}
+
+/// Return `result`, removing `temp` first if it is an error.
+async fn or_discard<F, T>(
+    fs: &F,
+    temp: &str,
+    options: &AtomicWriteOptions,
+    result: crate::Result<T>,
+) -> crate::Result<T>
+where
+    F: FileSystem + ?Sized,
+{
+    if result.is_err() {
+        discard(fs, temp, options).await;
+    }
+    result
+}
+
+/// Rename `temp` over `path`, removing `temp` if it fails.
+async fn rename_over<F>(
+    fs: &F,
+    temp: &str,
+    path: &str,
+    options: &AtomicWriteOptions,
+) -> crate::Result<()>
+where
+    F: FileSystem + ?Sized,
+{
+    let renamed = fs.rename_file(temp, path, options.fs_options()).await;
+    or_discard(fs, temp, options, renamed).await
+}
+
+/// Copy `path` to its backup, through its own temporary file so concurrent backups can't be interleaved.
+async fn back_up<F>(fs: &F, path: &str, options: &AtomicWriteOptions) -> crate::Result<()>
+where
+    F: FileSystem + ?Sized,
+{
+    let backup = backup_path(path);
+    let temp = temp_path(&backup);
+    let copied = fs.copy_file(path, &temp, options.fs_options()).await;
+    or_discard(fs, &temp, options, copied).await?;
+    rename_over(fs, &temp, &backup, options).await
+}
+
+/// Swap the already written `temp` file in, backing up the current version first if asked.
+///
+/// `temp` is removed if any step fails.
+async fn swap<F>(fs: &F, temp: &str, path: &str, options: &AtomicWriteOptions) -> crate::Result<()>
+where
+    F: FileSystem + ?Sized,
+{
+    // The backup is a copy, so a valid file stays at `path` for the whole swap.
+    let backed_up = async {
+        if options.backup && fs.exists(path, options.fs_options()).await? {
+            back_up(fs, path, options).await?;
+        }
+        crate::Result::Ok(())
+    }
+    .await;
+    or_discard(fs, temp, options, backed_up).await?;
+    rename_over(fs, temp, path, options).await
+}
+
+/// Atomically replace the contents of the `path` text file.
+pub async fn write_text_atomic(
+    path: &str,
+    contents: &str,
+    options: AtomicWriteOptions,
+) -> crate::Result<()> {
+    write_text_atomic_in(&TauriFs, path, contents, options).await
+}
+
+/// [`write_text_atomic`] through `fs`.
+pub async fn write_text_atomic_in<F>(
+    fs: &F,
+    path: &str,
+    contents: &str,
+    options: AtomicWriteOptions,
+) -> crate::Result<()>
+where
+    F: FileSystem + ?Sized,
+{
+    let temp = temp_path(path);
+    let written = fs
+        .write_text_file(&temp, contents, options.fs_options())
+        .await;
+    or_discard(fs, &temp, &options, written).await?;
+    swap(fs, &temp, path, &options).await
+}
+
+/// Atomically replace the contents of the `path` binary file.
+pub async fn write_binary_atomic(
+    path: &str,
+    contents: impl AsRef<[u8]>,
+    options: AtomicWriteOptions,
+) -> crate::Result<()> {
+    write_binary_atomic_in(&TauriFs, path, contents.as_ref(), options).await
+}
+
+/// [`write_binary_atomic`] through `fs`.
+pub async fn write_binary_atomic_in<F>(
+    fs: &F,
+    path: &str,
+    contents: &[u8],
+    options: AtomicWriteOptions,
+) -> crate::Result<()>
+where
+    F: FileSystem + ?Sized,
+{
+    let temp = temp_path(path);
+    let written = fs
+        .write_binary_file(&temp, contents, options.fs_options())
+        .await;
+    or_discard(fs, &temp, &options, written).await?;
+    swap(fs, &temp, path, &options).await
+}
+
+/// What [`recover`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Recovery {
+    /// The temporary files left by interrupted writes were removed.
+    pub removed_temp: bool,
+    /// The file was missing and has been restored from its backup.
+    pub restored_backup: bool,
+}
+
+/// Clean up after an interrupted atomic write of `path`, meant to be called at startup.
+///
+/// The leftover temporary files can't be trusted to be complete, so they are removed.
+/// This must not run while another window may be writing the file, or its write would fail.
+/// If the file itself is missing but a backup exists, the backup is restored.
+pub async fn recover(path: &str, options: AtomicWriteOptions) -> crate::Result<Recovery> {
+    recover_in(&TauriFs, path, options).await
+}
+
+/// [`recover`] through `fs`.
+pub async fn recover_in<F>(
+    fs: &F,
+    path: &str,
+    options: AtomicWriteOptions,
+) -> crate::Result<Recovery>
+where
+    F: FileSystem + ?Sized,
+{
+    let mut recovery = Recovery::default();
+    let (parent, file) = split_parent(path);
+    if fs.exists(parent, options.fs_options()).await? {
+        for entry in fs.read_dir(parent, options.fs_dir_options()).await? {
+            let is_temp = entry
+                .name
+                .as_deref()
+                .is_some_and(|name| is_temp_of(name, file));
+            if is_temp && entry.children.is_none() {
+                let temp = match parent {
+                    "" => entry.name.unwrap_or_default(),
+                    parent => format!("{parent}/{}", entry.name.unwrap_or_default()),
+                };
+                fs.remove_file(&temp, options.fs_options()).await?;
+                recovery.removed_temp = true;
+            }
+        }
+    }
+    if !fs.exists(path, options.fs_options()).await? {
+        recovery.restored_backup = restore_backup_in(fs, path, options).await?;
+    }
+    Ok(recovery)
+}
+
+/// Replace `path` with its backup, for example when its contents turn out to be corrupted.
+///
+/// Returns `false` if there is no backup.
+pub async fn restore_backup(path: &str, options: AtomicWriteOptions) -> crate::Result<bool> {
+    restore_backup_in(&TauriFs, path, options).await
+}
+
+/// [`restore_backup`] through `fs`.
+pub async fn restore_backup_in<F>(
+    fs: &F,
+    path: &str,
+    options: AtomicWriteOptions,
+) -> crate::Result<bool>
+where
+    F: FileSystem + ?Sized,
+{
+    let backup = backup_path(path);
+    if !fs.exists(&backup, options.fs_options()).await? {
+        return Ok(false);
+    }
+    // Go through a temporary file, so the backup is kept and the swap stays atomic.
+    let temp = temp_path(path);
+    let copied = fs.copy_file(&backup, &temp, options.fs_options()).await;
+    or_discard(fs, &temp, &options, copied).await?;
+    rename_over(fs, &temp, path, &options).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::{
+        backup_path, is_temp_of, recover_in, temp_path, write_text_atomic_in, AtomicWriteOptions,
+        Recovery,
+    };
+    use crate::fs::{system::FileSystem, BaseDirectory, FsDirOptions, FsOptions, InMemoryFs};
+
+    fn options() -> AtomicWriteOptions {
+        AtomicWriteOptions {
+            dir: Some(BaseDirectory::AppData),
+            backup: true,
+        }
+    }
+
+    fn fs_options() -> Option<FsOptions> {
+        options().fs_options()
+    }
+
+    #[test]
+    fn naming() {
+        assert_eq!(backup_path("a/b.json"), "a/b.json.bak");
+        let temp = temp_path("a/b.json");
+        assert_ne!(temp, temp_path("a/b.json"));
+        let name = temp.strip_prefix("a/").unwrap();
+        assert!(is_temp_of(name, "b.json"));
+        assert!(is_temp_of("b.json.tmp", "b.json"));
+        assert!(!is_temp_of("b.json", "b.json"));
+        assert!(!is_temp_of("b.json.bak", "b.json"));
+        assert!(!is_temp_of("b.json.notes.tmp", "b.json"));
+        assert!(!is_temp_of(name, "b"));
+        assert!(!is_temp_of(&temp_path("c.json"), "b.json"));
+    }
+
+    #[test]
+    fn write() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            write_text_atomic_in(&fs, "a.json", "1", options())
+                .await
+                .unwrap();
+            write_text_atomic_in(&fs, "a.json", "2", options())
+                .await
+                .unwrap();
+            assert_eq!(
+                fs.read_text_file("a.json", fs_options()).await.unwrap(),
+                "2"
+            );
+            assert_eq!(
+                fs.read_text_file("a.json.bak", fs_options()).await.unwrap(),
+                "1"
+            );
+            let dir_options = Some(FsDirOptions {
+                dir: Some(BaseDirectory::AppData),
+                recursive: Some(false),
+            });
+            assert_eq!(fs.read_dir("", dir_options).await.unwrap().len(), 2);
+        });
+    }
+
+    #[test]
+    fn failed_write() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            fs.write_text_file("a.json", "1", fs_options())
+                .await
+                .unwrap();
+            // The backup copy can't read the current file.
+            fs.deny_reads("a.json", Some(BaseDirectory::AppData))
+                .unwrap();
+            assert!(write_text_atomic_in(&fs, "a.json", "2", options())
+                .await
+                .is_err());
+            let dir_options = Some(FsDirOptions {
+                dir: Some(BaseDirectory::AppData),
+                recursive: Some(false),
+            });
+            let entries = fs.read_dir("", dir_options).await.unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].name.as_deref(), Some("a.json"));
+        });
+    }
+
+    #[test]
+    fn recover() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            // Nothing to do.
+            assert_eq!(
+                recover_in(&fs, "a.json", options()).await.unwrap(),
+                Recovery::default()
+            );
+
+            // Interrupted before the swap: the leftover temporary files go, the other files stay.
+            fs.write_text_file("a.json", "1", fs_options())
+                .await
+                .unwrap();
+            fs.write_text_file(&temp_path("a.json"), "2", fs_options())
+                .await
+                .unwrap();
+            fs.write_text_file("a.json.tmp", "2", fs_options())
+                .await
+                .unwrap();
+            fs.write_text_file(&temp_path("b.json"), "", fs_options())
+                .await
+                .unwrap();
+            let recovery = recover_in(&fs, "a.json", options()).await.unwrap();
+            assert_eq!(
+                recovery,
+                Recovery {
+                    removed_temp: true,
+                    restored_backup: false
+                }
+            );
+            assert_eq!(
+                fs.read_text_file("a.json", fs_options()).await.unwrap(),
+                "1"
+            );
+            let dir_options = Some(FsDirOptions {
+                dir: Some(BaseDirectory::AppData),
+                recursive: Some(false),
+            });
+            assert_eq!(fs.read_dir("", dir_options).await.unwrap().len(), 2);
+
+            // The file is missing: its backup is restored.
+            fs.remove_file("a.json", fs_options()).await.unwrap();
+            fs.write_text_file("a.json.bak", "0", fs_options())
+                .await
+                .unwrap();
+            let recovery = recover_in(&fs, "a.json", options()).await.unwrap();
+            assert!(recovery.restored_backup);
+            assert_eq!(
+                fs.read_text_file("a.json", fs_options()).await.unwrap(),
+                "0"
+            );
+            assert!(fs.exists("a.json.bak", fs_options()).await.unwrap());
+
+            // In a subdirectory that doesn't exist yet.
+            assert_eq!(
+                recover_in(&fs, "missing/a.json", options()).await.unwrap(),
+                Recovery::default()
+            );
+        });
+    }
+}
@@ -0,0 +1,429 @@
+//! Recursive copy and move of directories.
+//!
+//! Built on [`read_dir`](super::read_dir), [`create_dir`](super::create_dir),
+//! [`copy_file`](super::copy_file) and [`remove_dir`](super::remove_dir):
+//! the source tree is first listed into a plan, which is then either reported ([`CopyDirOptions::dry_run`])
+//! or applied entry by entry.
+use std::{collections::VecDeque, fmt, rc::Rc};
+
+use super::{
+    dir::join_relative, glob::GlobPattern, walk::join, BaseDirectory, FsDirOptions, FsOptions,
+};
+use crate::path::{Component, PathStyle, TauriPath, TauriPathBuf};
+
+/// What to do when a destination file already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverwritePolicy {
+    /// Fail with [`Error::AlreadyExists`](crate::Error::AlreadyExists) before copying anything.
+    ///
+    /// A dry run reports every existing file in [`CopyReport::conflicting_files`] instead.
+    #[default]
+    Error,
+    /// Keep the existing file.
+    Skip,
+    /// Replace the existing file.
+    Overwrite,
+}
+
+/// The progress of a [`copy_dir`] or [`move_dir`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CopyProgress {
+    /// The number of files handled so far, including the skipped ones.
+    pub done: usize,
+    pub total: usize,
+    /// The file just handled, relative to the source directory.
+    pub path: String,
+}
+
+/// A callback receiving the [`CopyProgress`].
+pub type ProgressCallback = Rc<dyn Fn(&CopyProgress)>;
+
+/// Options for [`copy_dir`] and [`move_dir`].
+#[derive(Clone, Default)]
+pub struct CopyDirOptions {
+    /// The base directory of both the source and the destination.
+    pub dir: Option<BaseDirectory>,
+    pub overwrite: OverwritePolicy,
+    /// The entries whose path relative to the source matches one of these patterns are left out.
+    pub exclude: Vec<GlobPattern>,
+    /// Only compute the [`CopyReport`], without touching the file system.
+    pub dry_run: bool,
+    /// Called after each file.
+    pub on_progress: Option<ProgressCallback>,
+}
+
+impl fmt::Debug for CopyDirOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyDirOptions")
+            .field("dir", &self.dir)
+            .field("overwrite", &self.overwrite)
+            .field("exclude", &self.exclude)
+            .field("dry_run", &self.dry_run)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl CopyDirOptions {
+    fn fs_options(&self) -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: self.dir,
+            ..Default::default()
+        })
+    }
+
+    fn dir_options(&self, recursive: bool) -> Option<FsDirOptions> {
+        Some(FsDirOptions {
+            dir: self.dir,
+            recursive: Some(recursive),
+        })
+    }
+}
+
+/// What [`copy_dir`] or [`move_dir`] did, or would do in a dry run.
+///
+/// Every path is relative to the source directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CopyReport {
+    /// The directories created in the destination.
+    pub created_dirs: Vec<String>,
+    /// The files copied, overwritten ones included.
+    pub copied_files: Vec<String>,
+    /// The files already existing in the destination.
+    pub overwritten_files: Vec<String>,
+    /// The files kept as is because of [`OverwritePolicy::Skip`].
+    pub skipped_files: Vec<String>,
+    /// The files already existing in the destination which fail the copy with [`OverwritePolicy::Error`],
+    /// only filled by a dry run.
+    pub conflicting_files: Vec<String>,
+    /// The entries matching [`CopyDirOptions::exclude`].
+    pub excluded: Vec<String>,
+}
+
+/// The listed source tree.
+#[derive(Debug, Default)]
+struct Plan {
+    dirs: Vec<String>,
+    files: Vec<String>,
+    excluded: Vec<String>,
+}
+
+async fn plan(src: &str, options: &CopyDirOptions) -> crate::Result<Plan> {
+    let mut plan = Plan::default();
+    let mut queue = VecDeque::from([String::new()]);
+    while let Some(relative_dir) = queue.pop_front() {
+        let entries =
+            super::read_dir(&join(src, &relative_dir), options.dir_options(false)).await?;
+        for entry in entries {
+            let Some(name) = entry.name else {
+                continue;
+            };
+            let relative_path = join_relative(&relative_dir, &name);
+            if options
+                .exclude
+                .iter()
+                .any(|pattern| pattern.matches(&relative_path))
+            {
+                plan.excluded.push(relative_path);
+            } else if entry.children.is_some() {
+                queue.push_back(relative_path.clone());
+                plan.dirs.push(relative_path);
+            } else {
+                plan.files.push(relative_path);
+            }
+        }
+    }
+    Ok(plan)
+}
+
+/// Reject a `dst` equal to `src` or inside it, which would copy the tree onto itself.
+///
+/// Both paths are normalized first, with `/` and `\` as separators like the other fs helpers.
+fn check_distinct(src: &str, dst: &str) -> crate::Result<()> {
+    fn components(path: &TauriPathBuf) -> Vec<Component<'_>> {
+        let mut components = path.as_path().components();
+        components.retain(|component| *component != Component::CurDir);
+        components
+    }
+    let normalized_src = TauriPath::with_style(src, PathStyle::Windows).normalize();
+    let normalized_dst = TauriPath::with_style(dst, PathStyle::Windows).normalize();
+    if components(&normalized_dst).starts_with(&components(&normalized_src)) {
+        return Err(crate::Error::InvalidPath {
+            path: dst.to_string(),
+            reason: "the destination can't be the source directory or be inside it",
+        });
+    }
+    Ok(())
+}
+
+/// The source directories left empty once the moved files are removed, deepest first.
+///
+/// A directory is kept if one of the `kept` paths is inside it.
+fn removable_dirs(dirs: &[String], kept: &[String]) -> Vec<String> {
+    let mut removable = dirs
+        .iter()
+        .filter(|dir| {
+            !kept.iter().any(|path| {
+                path.strip_prefix(dir.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    removable.sort_by_key(|dir| std::cmp::Reverse(dir.matches('/').count()));
+    removable
+}
+
+/// The entries created in the destination, removed on failure.
+#[derive(Default)]
+struct Rollback {
+    root: bool,
+    dirs: Vec<String>,
+    files: Vec<String>,
+}
+
+impl Rollback {
+    async fn run(self, dst: &str, options: &CopyDirOptions) {
+        for file in self.files.iter().rev() {
+            if let Err(_err) = super::remove_file(&join(dst, file), options.fs_options()).await {
+                #[cfg(feature = "log")]
+                log::error!("Cannot roll back the copy of {file}: {_err}");
+            }
+        }
+        for dir in self.dirs.iter().rev() {
+            if let Err(_err) = super::remove_dir(&join(dst, dir), options.dir_options(false)).await
+            {
+                #[cfg(feature = "log")]
+                log::error!("Cannot roll back the creation of {dir}: {_err}");
+            }
+        }
+        if self.root {
+            if let Err(_err) = super::remove_dir(dst, options.dir_options(false)).await {
+                #[cfg(feature = "log")]
+                log::error!("Cannot roll back the creation of {dst}: {_err}");
+            }
+        }
+    }
+}
+
+/// Fill `report` with what copying `files` would do, `existing` being the ones already in the destination.
+fn dry_run(
+    report: &mut CopyReport,
+    files: Vec<String>,
+    existing: &[String],
+    overwrite: OverwritePolicy,
+) {
+    for file in files {
+        match (existing.contains(&file), overwrite) {
+            (true, OverwritePolicy::Error) => report.conflicting_files.push(file),
+            (true, OverwritePolicy::Skip) => report.skipped_files.push(file),
+            (true, OverwritePolicy::Overwrite) => {
+                report.overwritten_files.push(file.clone());
+                report.copied_files.push(file);
+            }
+            (false, _) => report.copied_files.push(file),
+        }
+    }
+}
+
+async fn apply(
+    src: &str,
+    dst: &str,
+    plan: Plan,
+    options: &CopyDirOptions,
+) -> crate::Result<CopyReport> {
+    let mut report = CopyReport {
+        excluded: plan.excluded,
+        ..Default::default()
+    };
+    let dst_exists = super::exists(dst, options.fs_options()).await?;
+    // Conflicts are detected before anything is copied.
+    let mut existing = Vec::new();
+    if dst_exists {
+        for file in &plan.files {
+            if super::exists(&join(dst, file), options.fs_options()).await? {
+                existing.push(file.clone());
+            }
+        }
+    }
+    let mut missing_dirs = Vec::new();
+    for dir in &plan.dirs {
+        if !dst_exists || !super::exists(&join(dst, dir), options.fs_options()).await? {
+            missing_dirs.push(dir.clone());
+        }
+    }
+    if options.dry_run {
+        dry_run(&mut report, plan.files, &existing, options.overwrite);
+        report.created_dirs = missing_dirs;
+        return Ok(report);
+    }
+    if options.overwrite == OverwritePolicy::Error {
+        if let Some(file) = existing.first() {
+            return Err(crate::Error::AlreadyExists(join(dst, file)));
+        }
+    }
+
+    let mut rollback = Rollback::default();
+    let result = async {
+        if !dst_exists {
+            super::create_dir(dst, options.dir_options(true)).await?;
+            rollback.root = true;
+        }
+        for dir in missing_dirs {
+            super::create_dir(&join(dst, &dir), options.dir_options(false)).await?;
+            rollback.dirs.push(dir.clone());
+            report.created_dirs.push(dir);
+        }
+        let total = plan.files.len();
+        for (index, file) in plan.files.into_iter().enumerate() {
+            let overwrite = existing.contains(&file);
+            if overwrite && options.overwrite == OverwritePolicy::Skip {
+                report.skipped_files.push(file.clone());
+            } else {
+                super::copy_file(&join(src, &file), &join(dst, &file), options.fs_options())
+                    .await?;
+                if overwrite {
+                    report.overwritten_files.push(file.clone());
+                } else {
+                    rollback.files.push(file.clone());
+                }
+                report.copied_files.push(file.clone());
+            }
+            if let Some(on_progress) = &options.on_progress {
+                on_progress(&CopyProgress {
+                    done: index + 1,
+                    total,
+                    path: file,
+                });
+            }
+        }
+        crate::Result::Ok(())
+    }
+    .await;
+    match result {
+        Ok(()) => Ok(report),
+        Err(error) => {
+            rollback.run(dst, options).await;
+            Err(error)
+        }
+    }
+}
+
+/// Recursively copy the `src` directory into `dst`, creating it if needed.
+///
+/// `dst` must not be `src` nor be inside it, or [`Error::InvalidPath`](crate::Error::InvalidPath) is returned.
+/// If a step fails, the files and directories created so far are removed.
+/// The files replaced with [`OverwritePolicy::Overwrite`] can't be restored though.
+///
+/// ```rust,no_run
+/// use tauri_global_sys::fs::{copy::{copy_dir, CopyDirOptions}, BaseDirectory, GlobPattern};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let options = CopyDirOptions {
+///     dir: Some(BaseDirectory::AppData),
+///     exclude: vec![GlobPattern::new("**/*.lock")?],
+///     ..Default::default()
+/// };
+/// let report = copy_dir("profiles/default", "backups/default", options).await?;
+/// println!("{} files copied", report.copied_files.len());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn copy_dir(src: &str, dst: &str, options: CopyDirOptions) -> crate::Result<CopyReport> {
+    check_distinct(src, dst)?;
+    let plan = plan(src, &options).await?;
+    apply(src, dst, plan, &options).await
+}
+
+/// Recursively move the `src` directory into `dst`.
+///
+/// The tree is [copied](copy_dir) and the source entries are then removed,
+/// except for the excluded and skipped ones (and the directories containing them).
+pub async fn move_dir(src: &str, dst: &str, options: CopyDirOptions) -> crate::Result<CopyReport> {
+    check_distinct(src, dst)?;
+    let plan = plan(src, &options).await?;
+    let dirs = plan.dirs.clone();
+    let report = apply(src, dst, plan, &options).await?;
+    if options.dry_run {
+        return Ok(report);
+    }
+    for file in &report.copied_files {
+        super::remove_file(&join(src, file), options.fs_options()).await?;
+    }
+    let kept = report
+        .excluded
+        .iter()
+        .chain(&report.skipped_files)
+        .cloned()
+        .collect::<Vec<_>>();
+    for dir in removable_dirs(&dirs, &kept) {
+        super::remove_dir(&join(src, &dir), options.dir_options(false)).await?;
+    }
+    if kept.is_empty() {
+        super::remove_dir(src, options.dir_options(false)).await?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_distinct, dry_run, removable_dirs, CopyReport, OverwritePolicy};
+
+    #[test]
+    fn overlapping_paths() {
+        for (src, dst) in [
+            ("a", "a"),
+            ("a", "./a/"),
+            ("a/b", "a\\b"),
+            ("a", "a/b"),
+            ("a", "a/./b/../c"),
+            ("/data/a", "/data/a/b"),
+            (".", "b"),
+        ] {
+            assert!(
+                matches!(
+                    check_distinct(src, dst),
+                    Err(crate::Error::InvalidPath { .. })
+                ),
+                "{src} -> {dst}"
+            );
+        }
+        for (src, dst) in [("a", "ab"), ("a/b", "a"), ("a", "b/a"), ("a", "a/../b")] {
+            assert!(check_distinct(src, dst).is_ok(), "{src} -> {dst}");
+        }
+    }
+
+    #[test]
+    fn removable() {
+        let dirs = ["a", "a/b", "a/b/c", "ab", "d"].map(String::from);
+        let kept = ["a/b/keep.txt", "d/excluded"].map(String::from);
+        assert_eq!(
+            removable_dirs(&dirs, &kept),
+            ["a/b/c", "ab"].map(String::from)
+        );
+        assert_eq!(
+            removable_dirs(&dirs, &[]),
+            ["a/b/c", "a/b", "a", "ab", "d"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn dry_run_conflicts() {
+        let files = || ["a", "b", "c"].map(String::from).to_vec();
+        let existing = ["a", "c"].map(String::from);
+        let mut report = CopyReport::default();
+        dry_run(&mut report, files(), &existing, OverwritePolicy::Error);
+        assert_eq!(report.conflicting_files, existing);
+        assert_eq!(report.copied_files, ["b"]);
+
+        let mut report = CopyReport::default();
+        dry_run(&mut report, files(), &existing, OverwritePolicy::Skip);
+        assert!(report.conflicting_files.is_empty());
+        assert_eq!(report.skipped_files, existing);
+
+        let mut report = CopyReport::default();
+        dry_run(&mut report, files(), &existing, OverwritePolicy::Overwrite);
+        assert_eq!(report.overwritten_files, existing);
+        assert_eq!(report.copied_files, files());
+    }
+}
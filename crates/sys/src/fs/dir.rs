@@ -0,0 +1,249 @@
+use super::{BaseDirectory, FileEntry, FsDirOptions, FsOptions};
+
+/// Check that `path` can be used with the fs APIs:
+/// it must be relative and must not contain any `..` component.
+///
+/// The check is done locally, so an invalid path never reaches the IPC.
+pub fn check_relative_path(path: &str) -> crate::Result<()> {
+    let invalid = |reason: &'static str| {
+        Err(crate::Error::InvalidPath {
+            path: path.to_string(),
+            reason,
+        })
+    };
+    if path.starts_with(['/', '\\']) {
+        return invalid("absolute paths are not allowed");
+    }
+    let mut chars = path.chars();
+    if let (Some(drive), Some(':')) = (chars.next(), chars.next()) {
+        if drive.is_ascii_alphabetic() {
+            return invalid("absolute paths are not allowed");
+        }
+    }
+    if path.split(['/', '\\']).any(|component| component == "..") {
+        return invalid("parent directory components (`..`) are not allowed");
+    }
+    Ok(())
+}
+
+/// Join relative path segments with `/`, skipping the empty and `.` components.
+pub(crate) fn join_relative(base: &str, path: &str) -> String {
+    base.split(['/', '\\'])
+        .chain(path.split(['/', '\\']))
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A directory handle scoped to a [`BaseDirectory`].
+///
+/// Every operation fills in the base directory and rejects absolute or `..` paths
+/// (with [`Error::InvalidPath`](crate::Error::InvalidPath)) before reaching the IPC.
+///
+/// ```rust,no_run
+/// use tauri_global_sys::fs::{BaseDirectory, Dir};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let databases = Dir::new(BaseDirectory::AppData).sub_dir("databases")?;
+/// databases.create_dir_all("").await?;
+/// databases.write_text("main.json", "{}").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dir {
+    base: BaseDirectory,
+    prefix: String,
+}
+
+impl Dir {
+    pub fn new(base: BaseDirectory) -> Self {
+        Self {
+            base,
+            prefix: String::new(),
+        }
+    }
+
+    pub fn base_dir(&self) -> BaseDirectory {
+        self.base
+    }
+
+    /// The path of this directory, relative to its [`BaseDirectory`].
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// A handle to the `path` sub directory.
+    pub fn sub_dir(&self, path: &str) -> crate::Result<Self> {
+        Ok(Self {
+            base: self.base,
+            prefix: self.path(path)?,
+        })
+    }
+
+    /// The checked `path`, relative to the [`BaseDirectory`].
+    pub fn path(&self, path: &str) -> crate::Result<String> {
+        check_relative_path(path)?;
+        Ok(join_relative(&self.prefix, path))
+    }
+
+    /// The checked `path` of a destructive operation, which must name an entry inside this directory:
+    /// the empty and `.` paths, which would target the directory itself, are rejected.
+    fn entry_path(&self, path: &str) -> crate::Result<String> {
+        let checked = self.path(path)?;
+        if join_relative("", path).is_empty() {
+            return Err(crate::Error::InvalidPath {
+                path: path.to_string(),
+                reason: "the directory itself can't be removed or renamed",
+            });
+        }
+        Ok(checked)
+    }
+
+    fn options(&self) -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: Some(self.base),
+            ..Default::default()
+        })
+    }
+
+    fn dir_options(&self, recursive: bool) -> Option<FsDirOptions> {
+        Some(FsDirOptions {
+            dir: Some(self.base),
+            recursive: Some(recursive),
+        })
+    }
+
+    pub async fn read_text(&self, path: &str) -> crate::Result<String> {
+        super::read_text_file(&self.path(path)?, self.options()).await
+    }
+
+    pub async fn read_binary(&self, path: &str) -> crate::Result<Vec<u8>> {
+        super::read_binary_file(&self.path(path)?, self.options()).await
+    }
+
+    pub async fn write_text(&self, path: &str, contents: &str) -> crate::Result<()> {
+        super::write_text_file(&self.path(path)?, contents, self.options()).await
+    }
+
+    /// Append `contents` at the end of the file.
+    ///
+    /// Since Tauri 1.5.0
+    pub async fn append_text(&self, path: &str, contents: &str) -> crate::Result<()> {
+        let options = Some(FsOptions {
+            append: Some(true),
+            dir: Some(self.base),
+        });
+        super::write_text_file(&self.path(path)?, contents, options).await
+    }
+
+    pub async fn write_binary(&self, path: &str, contents: impl AsRef<[u8]>) -> crate::Result<()> {
+        super::write_binary_file(&self.path(path)?, contents, self.options()).await
+    }
+
+    /// List the entries of the `path` directory.
+    pub async fn read_dir(&self, path: &str, recursive: bool) -> crate::Result<Vec<FileEntry>> {
+        super::read_dir(&self.path(path)?, self.dir_options(recursive)).await
+    }
+
+    /// Create the `path` directory, its parent must exist.
+    pub async fn create_dir(&self, path: &str) -> crate::Result<()> {
+        super::create_dir(&self.path(path)?, self.dir_options(false)).await
+    }
+
+    /// Create the `path` directory and all of its missing parents.
+    pub async fn create_dir_all(&self, path: &str) -> crate::Result<()> {
+        super::create_dir(&self.path(path)?, self.dir_options(true)).await
+    }
+
+    /// Remove the `path` file.
+    pub async fn remove(&self, path: &str) -> crate::Result<()> {
+        super::remove_file(&self.entry_path(path)?, self.options()).await
+    }
+
+    /// Remove the `path` directory, it must be empty.
+    pub async fn remove_dir(&self, path: &str) -> crate::Result<()> {
+        super::remove_dir(&self.entry_path(path)?, self.dir_options(false)).await
+    }
+
+    /// Remove the `path` directory and all of its content.
+    ///
+    /// `path` must not be empty or `.`: a [`Dir`] can't remove itself.
+    pub async fn remove_dir_all(&self, path: &str) -> crate::Result<()> {
+        super::remove_dir(&self.entry_path(path)?, self.dir_options(true)).await
+    }
+
+    pub async fn rename(&self, from: &str, to: &str) -> crate::Result<()> {
+        super::rename_file(
+            &self.entry_path(from)?,
+            &self.entry_path(to)?,
+            self.options(),
+        )
+        .await
+    }
+
+    pub async fn copy(&self, from: &str, to: &str) -> crate::Result<()> {
+        super::copy_file(&self.path(from)?, &self.path(to)?, self.options()).await
+    }
+
+    pub async fn exists(&self, path: &str) -> crate::Result<bool> {
+        super::exists(&self.path(path)?, self.options()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_relative_path, Dir};
+    use crate::fs::BaseDirectory;
+
+    #[test]
+    fn relative_paths() {
+        assert!(check_relative_path("").is_ok());
+        assert!(check_relative_path("databases/main.db").is_ok());
+        assert!(check_relative_path("./a\\b.txt").is_ok());
+        assert!(check_relative_path("a..b/c").is_ok());
+    }
+
+    #[test]
+    fn rejected_paths() {
+        assert!(check_relative_path("/etc/passwd").is_err());
+        assert!(check_relative_path("\\\\server\\share").is_err());
+        assert!(check_relative_path("C:\\Users").is_err());
+        assert!(check_relative_path("c:/Users").is_err());
+        assert!(check_relative_path("..").is_err());
+        assert!(check_relative_path("a/../../b").is_err());
+        assert!(check_relative_path("a\\..\\b").is_err());
+    }
+
+    #[test]
+    fn sub_dir() {
+        let dir = Dir::new(BaseDirectory::AppData);
+        let databases = dir.sub_dir("databases/").unwrap();
+        assert_eq!(databases.prefix(), "databases");
+        assert_eq!(databases.path("main.db").unwrap(), "databases/main.db");
+        assert_eq!(
+            databases.sub_dir("./backups").unwrap().path("").unwrap(),
+            "databases/backups"
+        );
+        assert!(databases.sub_dir("../other").is_err());
+        assert!(databases.path("/main.db").is_err());
+        assert_eq!(dir.path("").unwrap(), "");
+    }
+
+    #[test]
+    fn entry_paths() {
+        let databases = Dir::new(BaseDirectory::AppData)
+            .sub_dir("databases")
+            .unwrap();
+        assert_eq!(databases.entry_path("a/").unwrap(), "databases/a");
+        for path in ["", ".", "./", "a/.."] {
+            assert!(
+                matches!(
+                    databases.entry_path(path),
+                    Err(crate::Error::InvalidPath { .. })
+                ),
+                "{path}"
+            );
+        }
+    }
+}
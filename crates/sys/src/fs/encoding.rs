@@ -0,0 +1,158 @@
+//! Read and write text files in other encodings than UTF-8.
+//!
+//! Built on [`read_binary_file`](super::read_binary_file) and [`write_binary_file`](super::write_binary_file),
+//! with [`encoding_rs`] for the decoding. Requires the `encoding` feature.
+pub use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+use super::FsOptions;
+
+/// A text file decoded by [`read_text_file_with_encoding`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// The encoding actually used, given by the BOM if there is one.
+    pub encoding: &'static Encoding,
+    pub had_bom: bool,
+    /// Whether malformed sequences were replaced with U+FFFD.
+    pub had_errors: bool,
+}
+
+/// Decode `bytes` as `encoding`, or detect it if [`None`].
+///
+/// A BOM always takes precedence. Without it, the detection falls back
+/// to UTF-8 if the bytes are valid UTF-8 and to Windows-1252 otherwise.
+pub fn decode(bytes: &[u8], encoding: Option<&'static Encoding>) -> DecodedText {
+    let (encoding, had_bom) = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => (encoding, true),
+        None => match encoding {
+            Some(encoding) => (encoding, false),
+            None if std::str::from_utf8(bytes).is_ok() => (UTF_8, false),
+            None => (WINDOWS_1252, false),
+        },
+    };
+    let (text, had_errors) = encoding.decode_with_bom_removal(bytes);
+    DecodedText {
+        text: text.into_owned(),
+        encoding,
+        had_bom,
+        had_errors,
+    }
+}
+
+fn bom(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == UTF_8 {
+        b"\xEF\xBB\xBF"
+    } else if encoding == UTF_16LE {
+        b"\xFF\xFE"
+    } else if encoding == UTF_16BE {
+        b"\xFE\xFF"
+    } else {
+        b""
+    }
+}
+
+/// Encode `text` as `encoding`, with a BOM for the Unicode encodings if `with_bom` is set.
+///
+/// Fails with [`Error::Encoding`](crate::Error::Encoding) if a character can't be represented.
+pub fn encode(text: &str, encoding: &'static Encoding, with_bom: bool) -> crate::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if with_bom {
+        bytes.extend_from_slice(bom(encoding));
+    }
+    // `encoding_rs` only decodes UTF-16, its encoder outputs UTF-8 for it.
+    if encoding == UTF_16LE {
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    } else if encoding == UTF_16BE {
+        bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    } else {
+        let (encoded, _, had_errors) = encoding.encode(text);
+        if had_errors {
+            return Err(crate::Error::Encoding(encoding.name()));
+        }
+        bytes.extend_from_slice(&encoded);
+    }
+    Ok(bytes)
+}
+
+/// Read a text file in `encoding`, or in the detected encoding if [`None`] (see [`decode`]).
+///
+/// ```rust,no_run
+/// use tauri_global_sys::fs::{encoding::read_text_file_with_encoding, BaseDirectory, FsOptions};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let options = FsOptions {
+///     dir: Some(BaseDirectory::Download),
+///     ..Default::default()
+/// };
+/// let csv = read_text_file_with_encoding("export.csv", None, Some(options)).await?;
+/// println!("{} decoded as {}", csv.text, csv.encoding.name());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn read_text_file_with_encoding(
+    path: &str,
+    encoding: Option<&'static Encoding>,
+    options: Option<FsOptions>,
+) -> crate::Result<DecodedText> {
+    let bytes = super::read_binary_file(path, options).await?;
+    Ok(decode(&bytes, encoding))
+}
+
+/// Write a text file in `encoding`, see [`encode`].
+pub async fn write_text_file_with_encoding(
+    path: &str,
+    contents: &str,
+    encoding: &'static Encoding,
+    with_bom: bool,
+    options: Option<FsOptions>,
+) -> crate::Result<()> {
+    let bytes = encode(contents, encoding, with_bom)?;
+    super::write_binary_file(path, bytes, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+    use super::{decode, encode};
+
+    #[test]
+    fn sniff_bom() {
+        let decoded = decode(b"\xFF\xFEa\0\xE9\0", None);
+        assert_eq!(decoded.text, "aé");
+        assert_eq!(decoded.encoding, UTF_16LE);
+        assert!(decoded.had_bom);
+
+        let decoded = decode(b"\xFE\xFF\0a\0\xE9", Some(WINDOWS_1252));
+        assert_eq!(decoded.text, "aé");
+        assert_eq!(decoded.encoding, UTF_16BE);
+
+        let decoded = decode(b"\xEF\xBB\xBFcaf\xC3\xA9", None);
+        assert_eq!(decoded.text, "café");
+        assert_eq!(decoded.encoding, UTF_8);
+    }
+
+    #[test]
+    fn detect_without_bom() {
+        let decoded = decode("café".as_bytes(), None);
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(!decoded.had_bom);
+
+        let decoded = decode(b"caf\xE9 \x80", None);
+        assert_eq!(decoded.text, "café €");
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+        assert!(!decoded.had_errors);
+    }
+
+    #[test]
+    fn round_trip() {
+        for encoding in [UTF_8, UTF_16LE, UTF_16BE, WINDOWS_1252] {
+            let bytes = encode("café €", encoding, true).unwrap();
+            let decoded = decode(&bytes, Some(encoding));
+            assert_eq!(decoded.text, "café €");
+            assert_eq!(decoded.encoding, encoding);
+        }
+        assert_eq!(encode("aé", UTF_16LE, true).unwrap(), b"\xFF\xFEa\0\xE9\0");
+        assert_eq!(encode("é", SHIFT_JIS, false).ok(), None);
+    }
+}
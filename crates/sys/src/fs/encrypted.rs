@@ -0,0 +1,373 @@
+//! Files encrypted at rest with ChaCha20-Poly1305.
+//!
+//! Built on [`read_binary_file`](super::read_binary_file) and [`write_binary_atomic`](super::atomic::write_binary_atomic).
+//! The key is either supplied directly, e.g. by the backend, or derived from a passphrase with Argon2id.
+//! Requires the `crypto` feature.
+//!
+//! The file starts with a header authenticated along with the ciphertext:
+//!
+//! | bytes | contents |
+//! |---|---|
+//! | 4 | the magic `TGSE` |
+//! | 1 | the format version |
+//! | 1 | the key derivation: 0 for a raw key, 1 for Argon2id |
+//! | 28 | for Argon2id only, the memory, iterations and parallelism costs as little endian `u32` and the salt |
+//! | 12 | the nonce |
+//! | 4 | the length of the metadata, as a little endian `u32` |
+//! | n | the metadata, authenticated but not encrypted |
+use std::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    ChaCha20Poly1305, KeyInit,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    atomic::{self, AtomicWriteOptions},
+    BaseDirectory, FsOptions,
+};
+
+const MAGIC: &[u8; 4] = b"TGSE";
+const VERSION: u8 = 1;
+const KDF_RAW: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// The maximum Argon2id memory cost, in KiB: 1 GiB.
+pub const MAX_M_COST: u32 = 1 << 20;
+/// The maximum Argon2id iterations.
+pub const MAX_T_COST: u32 = 16;
+/// The maximum Argon2id parallelism.
+pub const MAX_P_COST: u32 = 16;
+
+/// The key of an [`EncryptedFile`].
+#[derive(Clone, PartialEq, Eq)]
+pub enum EncryptionKey {
+    /// A 256 bits key.
+    Raw([u8; 32]),
+    /// A passphrase, stretched with Argon2id and a random salt on every write.
+    Passphrase(String),
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Raw(_) => f.write_str("Raw(..)"),
+            Self::Passphrase(_) => f.write_str("Passphrase(..)"),
+        }
+    }
+}
+
+/// The contents of an [`EncryptedFile`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decrypted {
+    pub data: Vec<u8>,
+    pub metadata: Vec<u8>,
+}
+
+fn random<const N: usize>() -> crate::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes).map_err(|_| crate::Error::Crypto("no random source"))?;
+    Ok(bytes)
+}
+
+/// Reject the costs above the caps, so a crafted file can't make the key derivation exhaust the memory or the CPU.
+fn check_params(params: &Params) -> crate::Result<()> {
+    if params.m_cost() > MAX_M_COST || params.t_cost() > MAX_T_COST || params.p_cost() > MAX_P_COST
+    {
+        return Err(crate::Error::Crypto("key derivation costs too high"));
+    }
+    Ok(())
+}
+
+fn argon2_key(passphrase: &str, salt: &[u8], params: Params) -> crate::Result<[u8; 32]> {
+    check_params(&params)?;
+    let mut key = [0; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|_| crate::Error::Crypto("key derivation failed"))?;
+    Ok(key)
+}
+
+fn seal(
+    key: &EncryptionKey,
+    params: Params,
+    data: &[u8],
+    metadata: &[u8],
+) -> crate::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(64 + metadata.len());
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    let key = match key {
+        EncryptionKey::Raw(key) => {
+            header.push(KDF_RAW);
+            *key
+        }
+        EncryptionKey::Passphrase(passphrase) => {
+            let salt = random::<SALT_LEN>()?;
+            header.push(KDF_ARGON2ID);
+            for cost in [params.m_cost(), params.t_cost(), params.p_cost()] {
+                header.extend_from_slice(&cost.to_le_bytes());
+            }
+            header.extend_from_slice(&salt);
+            argon2_key(passphrase, &salt, params)?
+        }
+    };
+    let nonce = random::<NONCE_LEN>()?;
+    header.extend_from_slice(&nonce);
+    let metadata_len =
+        u32::try_from(metadata.len()).map_err(|_| crate::Error::Crypto("metadata too long"))?;
+    header.extend_from_slice(&metadata_len.to_le_bytes());
+    header.extend_from_slice(metadata);
+    let ciphertext = ChaCha20Poly1305::new(&key.into())
+        .encrypt(
+            &nonce.into(),
+            Payload {
+                msg: data,
+                aad: &header,
+            },
+        )
+        .map_err(|_| crate::Error::Crypto("encryption failed"))?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// A cursor over the header.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(crate::Error::Crypto("truncated header"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> crate::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+fn open(key: &EncryptionKey, bytes: &[u8]) -> crate::Result<Decrypted> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(crate::Error::Crypto("not an encrypted file"));
+    }
+    let version = reader.take(1)?[0];
+    if version != VERSION {
+        return Err(crate::Error::UnsupportedVersion {
+            found: version.into(),
+            supported: VERSION.into(),
+        });
+    }
+    let key = match (reader.take(1)?[0], key) {
+        (KDF_RAW, EncryptionKey::Raw(key)) => *key,
+        (KDF_ARGON2ID, EncryptionKey::Passphrase(passphrase)) => {
+            let (m_cost, t_cost, p_cost) = (reader.u32()?, reader.u32()?, reader.u32()?);
+            let params = Params::new(m_cost, t_cost, p_cost, None)
+                .map_err(|_| crate::Error::Crypto("invalid key derivation parameters"))?;
+            let salt = reader.take(SALT_LEN)?;
+            argon2_key(passphrase, salt, params)?
+        }
+        (KDF_RAW | KDF_ARGON2ID, _) => {
+            return Err(crate::Error::Crypto(
+                "the key doesn't match the key derivation",
+            ))
+        }
+        _ => return Err(crate::Error::Crypto("unknown key derivation")),
+    };
+    let nonce = reader.take(NONCE_LEN)?;
+    let metadata_len = reader.u32()? as usize;
+    let metadata = reader.take(metadata_len)?;
+    let (header, ciphertext) = bytes.split_at(reader.position);
+    let data = ChaCha20Poly1305::new(&key.into())
+        .decrypt(
+            nonce.into(),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| crate::Error::Crypto("wrong key or tampered file"))?;
+    Ok(Decrypted {
+        data,
+        metadata: metadata.to_vec(),
+    })
+}
+
+/// A file encrypted with ChaCha20-Poly1305, along with authenticated plaintext metadata.
+///
+/// ```rust,no_run
+/// use tauri_global_sys::fs::{
+///     encrypted::{EncryptedFile, EncryptionKey},
+///     BaseDirectory,
+/// };
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let tokens = EncryptedFile::new(
+///     "tokens.bin",
+///     Some(BaseDirectory::AppData),
+///     EncryptionKey::Passphrase("correct horse battery staple".into()),
+/// );
+/// tokens.write_json(&["token"], b"api-v2").await?;
+/// let (tokens, _metadata): (Vec<String>, _) = tokens.read_json().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncryptedFile {
+    path: String,
+    dir: Option<BaseDirectory>,
+    key: EncryptionKey,
+    params: Params,
+}
+
+impl EncryptedFile {
+    pub fn new(path: impl Into<String>, dir: Option<BaseDirectory>, key: EncryptionKey) -> Self {
+        Self {
+            path: path.into(),
+            dir,
+            key,
+            params: Params::DEFAULT,
+        }
+    }
+
+    /// Set the Argon2id costs used by the next writes, the reads use the ones stored in the file.
+    ///
+    /// Defaults to [`Params::DEFAULT`]. The costs above [`MAX_M_COST`], [`MAX_T_COST`] and [`MAX_P_COST`]
+    /// are rejected by both the reads and the writes.
+    pub fn argon2_params(mut self, params: Params) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn options(&self) -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: self.dir,
+            ..Default::default()
+        })
+    }
+
+    /// Read and decrypt the file.
+    ///
+    /// Fails with [`Error::Crypto`](crate::Error::Crypto) if the key is wrong or if the file was modified.
+    pub async fn read(&self) -> crate::Result<Decrypted> {
+        let bytes = super::read_binary_file(&self.path, self.options()).await?;
+        open(&self.key, &bytes)
+    }
+
+    /// Encrypt and [atomically](atomic) write the file, with a new nonce and salt.
+    pub async fn write(&self, data: &[u8], metadata: &[u8]) -> crate::Result<()> {
+        let bytes = seal(&self.key, self.params.clone(), data, metadata)?;
+        let options = AtomicWriteOptions {
+            dir: self.dir,
+            backup: false,
+        };
+        atomic::write_binary_atomic(&self.path, bytes, options).await
+    }
+
+    pub async fn read_json<T: DeserializeOwned>(&self) -> crate::Result<(T, Vec<u8>)> {
+        let decrypted = self.read().await?;
+        Ok((serde_json::from_slice(&decrypted.data)?, decrypted.metadata))
+    }
+
+    pub async fn write_json<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+        metadata: &[u8],
+    ) -> crate::Result<()> {
+        self.write(&serde_json::to_vec(value)?, metadata).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use argon2::Params;
+
+    use super::{open, seal, EncryptionKey, MAX_M_COST};
+
+    fn params() -> Params {
+        Params::new(64, 1, 1, None).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        for key in [
+            EncryptionKey::Raw([7; 32]),
+            EncryptionKey::Passphrase("secret".into()),
+        ] {
+            let sealed = seal(&key, params(), b"token", b"meta").unwrap();
+            let opened = open(&key, &sealed).unwrap();
+            assert_eq!(opened.data, b"token");
+            assert_eq!(opened.metadata, b"meta");
+            assert_ne!(seal(&key, params(), b"token", b"meta").unwrap(), sealed);
+        }
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let key = EncryptionKey::Passphrase("secret".into());
+        let sealed = seal(&key, params(), b"token", b"meta").unwrap();
+
+        let wrong = EncryptionKey::Passphrase("guess".into());
+        assert!(matches!(
+            open(&wrong, &sealed),
+            Err(crate::Error::Crypto(_))
+        ));
+        assert!(matches!(
+            open(&EncryptionKey::Raw([0; 32]), &sealed),
+            Err(crate::Error::Crypto(_))
+        ));
+
+        let metadata_at = sealed.len() - 16 - b"token".len() - b"meta".len();
+        let mut tampered = sealed.clone();
+        tampered[metadata_at] ^= 1;
+        assert!(matches!(
+            open(&key, &tampered),
+            Err(crate::Error::Crypto(_))
+        ));
+
+        let mut future = sealed.clone();
+        future[4] = 2;
+        assert!(matches!(
+            open(&key, &future),
+            Err(crate::Error::UnsupportedVersion {
+                found: 2,
+                supported: 1
+            })
+        ));
+        assert!(open(&key, &sealed[..10]).is_err());
+    }
+
+    #[test]
+    fn rejects_costly_params() {
+        let key = EncryptionKey::Passphrase("secret".into());
+        let costly = Params::new(MAX_M_COST + 1, 1, 1, None).unwrap();
+        assert!(matches!(
+            seal(&key, costly, b"token", b""),
+            Err(crate::Error::Crypto(_))
+        ));
+
+        // The memory cost follows the magic, the version and the key derivation.
+        let mut sealed = seal(&key, params(), b"token", b"").unwrap();
+        sealed[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            open(&key, &sealed),
+            Err(crate::Error::Crypto("key derivation costs too high"))
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+//! A small glob pattern matcher for paths.
+//!
+//! Supported syntax:
+//! - `?` matches any single character,
+//! - `*` matches any sequence of characters,
+//! - `**` matches any sequence of characters including separators, `**/` also matches no directory at all,
+//! - `[abc]`, `[a-z]` and `[!abc]` match a character from (or not from) a set.
+//!
+//! Both `/` and `\` are treated as separators, in the pattern and in the matched paths.
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Token {
+    Char(char),
+    Separator,
+    AnyChar,
+    AnySequence,
+    AnyRecursive,
+    /// `**/`, matches either nothing or anything ending with a separator.
+    AnyDirectories,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// A compiled glob pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GlobPattern {
+    pattern: String,
+    tokens: Vec<Token>,
+    literal_separator: bool,
+}
+
+impl GlobPattern {
+    /// Compile `pattern`, where `*` and `?` don't match path separators.
+    pub fn new(pattern: &str) -> crate::Result<Self> {
+        let chars = pattern.chars().collect::<Vec<_>>();
+        let mut tokens = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            match chars[index] {
+                '?' => tokens.push(Token::AnyChar),
+                '*' if chars.get(index + 1) == Some(&'*') => {
+                    index += 1;
+                    if chars.get(index + 1).copied().is_some_and(is_separator) {
+                        index += 1;
+                        tokens.push(Token::AnyDirectories);
+                    } else {
+                        tokens.push(Token::AnyRecursive);
+                    }
+                }
+                '*' => tokens.push(Token::AnySequence),
+                '[' => {
+                    let end = chars[index + 1..]
+                        .iter()
+                        .skip(1)
+                        .position(|c| *c == ']')
+                        .map(|end| index + 2 + end)
+                        .ok_or_else(|| crate::Error::InvalidGlob {
+                            pattern: pattern.to_string(),
+                            reason: "unclosed character class",
+                        })?;
+                    let mut class = &chars[index + 1..end];
+                    let negated = matches!(class.first(), Some('!' | '^'));
+                    if negated {
+                        class = &class[1..];
+                    }
+                    let mut ranges = Vec::new();
+                    let mut class_index = 0;
+                    while class_index < class.len() {
+                        if class.get(class_index + 1) == Some(&'-') && class_index + 2 < class.len()
+                        {
+                            ranges.push((class[class_index], class[class_index + 2]));
+                            class_index += 3;
+                        } else {
+                            ranges.push((class[class_index], class[class_index]));
+                            class_index += 1;
+                        }
+                    }
+                    tokens.push(Token::Class { negated, ranges });
+                    index = end;
+                }
+                c if is_separator(c) => tokens.push(Token::Separator),
+                c => tokens.push(Token::Char(c)),
+            }
+            index += 1;
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+            tokens,
+            literal_separator: true,
+        })
+    }
+
+    /// Set whether `*` and `?` are prevented from matching path separators (`true` by default).
+    pub fn literal_separator(mut self, literal_separator: bool) -> Self {
+        self.literal_separator = literal_separator;
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the whole `path` matches the pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.chars().collect::<Vec<_>>();
+        let mut failed = HashSet::new();
+        self.matches_from(&path, 0, 0, &mut failed)
+    }
+
+    fn matches_from(
+        &self,
+        path: &[char],
+        token: usize,
+        position: usize,
+        failed: &mut HashSet<(usize, usize)>,
+    ) -> bool {
+        if failed.contains(&(token, position)) {
+            return false;
+        }
+        let matched = match self.tokens.get(token) {
+            None => position == path.len(),
+            Some(Token::AnySequence) => (position..=path.len())
+                .take_while(|end| {
+                    *end == position || !self.literal_separator || !is_separator(path[end - 1])
+                })
+                .any(|end| self.matches_from(path, token + 1, end, failed)),
+            Some(Token::AnyRecursive) => {
+                (position..=path.len()).any(|end| self.matches_from(path, token + 1, end, failed))
+            }
+            Some(Token::AnyDirectories) => {
+                self.matches_from(path, token + 1, position, failed)
+                    || (position + 1..=path.len())
+                        .filter(|end| is_separator(path[end - 1]))
+                        .any(|end| self.matches_from(path, token + 1, end, failed))
+            }
+            Some(single) => {
+                path.get(position)
+                    .is_some_and(|c| self.matches_char(single, *c))
+                    && self.matches_from(path, token + 1, position + 1, failed)
+            }
+        };
+        if !matched {
+            failed.insert((token, position));
+        }
+        matched
+    }
+
+    fn matches_char(&self, token: &Token, c: char) -> bool {
+        match token {
+            Token::Char(expected) => *expected == c,
+            Token::Separator => is_separator(c),
+            Token::AnyChar => !self.literal_separator || !is_separator(c),
+            Token::Class { negated, ranges } => {
+                !is_separator(c)
+                    && ranges
+                        .iter()
+                        .any(|(start, end)| (*start..=*end).contains(&c))
+                        != *negated
+            }
+            _ => false,
+        }
+    }
+}
+
+impl TryFrom<&str> for GlobPattern {
+    type Error = crate::Error;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlobPattern;
+
+    fn glob(pattern: &str) -> GlobPattern {
+        GlobPattern::new(pattern).unwrap()
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(glob("*.txt").matches("notes.txt"));
+        assert!(!glob("*.txt").matches("dir/notes.txt"));
+        assert!(glob("*.txt")
+            .literal_separator(false)
+            .matches("dir/notes.txt"));
+        assert!(glob("file?.log").matches("file1.log"));
+        assert!(!glob("file?.log").matches("file10.log"));
+        assert!(glob("databases/*").matches("databases/main.db"));
+        assert!(glob("databases\\*").matches("databases/main.db"));
+    }
+
+    #[test]
+    fn recursive() {
+        assert!(glob("**/*.rs").matches("main.rs"));
+        assert!(glob("**/*.rs").matches("src/fs/glob.rs"));
+        assert!(glob("src/**/*.rs").matches("src/lib.rs"));
+        assert!(glob("src/**").matches("src/fs/glob.rs"));
+        assert!(!glob("src/**/*.rs").matches("tests/lib.rs"));
+        assert!(glob("**/node_modules/**").matches("a/node_modules/b/c.js"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(glob("[abc].txt").matches("b.txt"));
+        assert!(!glob("[abc].txt").matches("d.txt"));
+        assert!(glob("[a-z]?.txt").matches("x1.txt"));
+        assert!(glob("[!a-z].txt").matches("1.txt"));
+        assert!(!glob("[!a-z].txt").matches("q.txt"));
+        assert!(GlobPattern::new("[abc").is_err());
+    }
+}
@@ -0,0 +1,418 @@
+//! Typed and versioned JSON files, for the settings stored by nearly every app.
+//!
+//! The value is stored in an envelope with its schema version:
+//!
+//! ```json
+//! { "version": 2, "data": { "theme": "dark" } }
+//! ```
+//!
+//! A JSON file without this envelope is read as the version 0 data, so existing hand-written files can be adopted.
+use std::{fmt, marker::PhantomData};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    atomic::{self, AtomicWriteOptions},
+    system::{FileSystem, TauriFs},
+    BaseDirectory, FsDirOptions, FsOptions,
+};
+
+type Migration = Box<dyn Fn(Value) -> crate::Result<Value>>;
+
+#[derive(Serialize)]
+struct EnvelopeRef<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Envelope {
+    version: u32,
+    data: Value,
+}
+
+/// How [`JsonFile::load_with_outcome`] got its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadOutcome {
+    /// The file was read as is.
+    Loaded,
+    /// The file was read and upgraded from an older version, then saved.
+    Migrated { from: u32 },
+    /// The file doesn't exist, the default value is used.
+    Missing,
+    /// The file was corrupted and its backup was used instead.
+    RestoredBackup,
+    /// The file was corrupted and its backup was missing or corrupted too, the default value is used.
+    Corrupted,
+}
+
+/// A JSON file holding a `T`, with a schema version and migrations.
+///
+/// The file is written [atomically](atomic) and its previous version is kept as a backup,
+/// which is used if the file turns out to be corrupted.
+/// It goes through the Tauri `fs` module, or any [`FileSystem`] given to [`JsonFile::with_fs`].
+///
+/// ```rust,no_run
+/// use serde::{Deserialize, Serialize};
+/// use tauri_global_sys::fs::{json::JsonFile, BaseDirectory};
+///
+/// #[derive(Debug, Default, Serialize, Deserialize)]
+/// struct Settings {
+///     theme: String,
+///     font_size: u32,
+/// }
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let settings = JsonFile::<Settings>::new("settings.json", BaseDirectory::AppConfig)
+///     // Version 0 stored the font size as a string.
+///     .migration(|mut value| {
+///         if let Some(size) = value.get("font_size").and_then(|size| size.as_str()) {
+///             value["font_size"] = size.parse::<u32>().unwrap_or(14).into();
+///         }
+///         Ok(value)
+///     });
+/// settings.update(|settings| settings.theme = "dark".into()).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct JsonFile<T, F = TauriFs> {
+    path: String,
+    dir: BaseDirectory,
+    fs: F,
+    migrations: Vec<Migration>,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T, F> fmt::Debug for JsonFile<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonFile")
+            .field("path", &self.path)
+            .field("dir", &self.dir)
+            .field("version", &self.migrations.len())
+            .finish()
+    }
+}
+
+/// Upgrade the raw file contents to the `data` of the latest version, returning it with its original version.
+fn upgrade(contents: Value, migrations: &[Migration]) -> crate::Result<(Value, u32)> {
+    let (mut data, version) = match serde_json::from_value::<Envelope>(contents.clone()) {
+        Ok(Envelope { version, data }) => (data, version),
+        Err(_) => (contents, 0),
+    };
+    let latest = migrations.len() as u32;
+    if version > latest {
+        return Err(crate::Error::UnsupportedVersion {
+            found: version,
+            supported: latest,
+        });
+    }
+    for migration in &migrations[version as usize..] {
+        data = migration(data)?;
+    }
+    Ok((data, version))
+}
+
+impl<T> JsonFile<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    pub fn new(path: &str, dir: BaseDirectory) -> Self {
+        Self::with_fs(path, dir, TauriFs)
+    }
+}
+
+impl<T, F> JsonFile<T, F>
+where
+    T: Serialize + DeserializeOwned + Default,
+    F: FileSystem,
+{
+    /// The JSON file at `path` in `fs`.
+    pub fn with_fs(path: &str, dir: BaseDirectory, fs: F) -> Self {
+        Self {
+            path: path.to_string(),
+            dir,
+            fs,
+            migrations: Vec::new(),
+            _value: PhantomData,
+        }
+    }
+
+    /// Register the migration upgrading the `data` of the current version to the next one.
+    ///
+    /// The schema version is the number of registered migrations:
+    /// the first one upgrades from version 0 to 1, the second one from 1 to 2, and so on.
+    pub fn migration<M>(mut self, migration: M) -> Self
+    where
+        M: Fn(Value) -> crate::Result<Value> + 'static,
+    {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// The current schema version.
+    pub fn version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
+    fn options(&self) -> AtomicWriteOptions {
+        AtomicWriteOptions {
+            dir: Some(self.dir),
+            backup: true,
+        }
+    }
+
+    fn fs_options(&self) -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: Some(self.dir),
+            ..Default::default()
+        })
+    }
+
+    async fn read(&self, path: &str) -> crate::Result<(T, u32)> {
+        let contents = self.fs.read_text_file(path, self.fs_options()).await?;
+        let (data, version) = upgrade(serde_json::from_str(&contents)?, &self.migrations)?;
+        Ok((serde_json::from_value(data)?, version))
+    }
+
+    /// Load the value, or the default one if the file doesn't exist or is corrupted.
+    pub async fn load(&self) -> crate::Result<T> {
+        Ok(self.load_with_outcome().await?.0)
+    }
+
+    /// Same as [`JsonFile::load`], also telling where the value comes from.
+    ///
+    /// Only a file that isn't valid JSON is replaced with its backup or the defaults:
+    /// a file written by a newer version of the app fails with [`Error::UnsupportedVersion`](crate::Error::UnsupportedVersion),
+    /// and any other error, like a denied read or valid JSON which doesn't match `T`, is returned as is.
+    pub async fn load_with_outcome(&self) -> crate::Result<(T, LoadOutcome)> {
+        atomic::recover_in(&self.fs, &self.path, self.options()).await?;
+        if !self.fs.exists(&self.path, self.fs_options()).await? {
+            return Ok((T::default(), LoadOutcome::Missing));
+        }
+        let (value, outcome) = match self.read(&self.path).await {
+            Ok((value, version)) if version == self.version() => {
+                return Ok((value, LoadOutcome::Loaded));
+            }
+            Ok((value, from)) => (value, LoadOutcome::Migrated { from }),
+            // Only a corrupted file is replaced, any other error leaves the file and its backup alone.
+            Err(error) if is_corrupted(&error) => {
+                #[cfg(feature = "log")]
+                log::error!("Cannot parse {}: {error}", self.path);
+                match self.read(&atomic::backup_path(&self.path)).await {
+                    Ok((value, _)) => (value, LoadOutcome::RestoredBackup),
+                    Err(error)
+                        if is_corrupted(&error) || error.kind() == crate::ErrorKind::NotFound =>
+                    {
+                        (T::default(), LoadOutcome::Corrupted)
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+            Err(error) => return Err(error),
+        };
+        // Persist the migrated or recovered value, the corrupted file becomes the backup.
+        self.save(&value).await?;
+        Ok((value, outcome))
+    }
+
+    /// Atomically write `value`, keeping the previous file as a backup.
+    ///
+    /// The parent directories are created if needed.
+    pub async fn save(&self, value: &T) -> crate::Result<()> {
+        let parent = self
+            .path
+            .rsplit_once(['/', '\\'])
+            .map_or("", |(parent, _)| parent);
+        let dir_options = FsDirOptions {
+            dir: Some(self.dir),
+            recursive: Some(true),
+        };
+        if !self.fs.exists(parent, self.fs_options()).await? {
+            self.fs.create_dir(parent, Some(dir_options)).await?;
+        }
+        let contents = serde_json::to_string_pretty(&EnvelopeRef {
+            version: self.version(),
+            data: value,
+        })?;
+        atomic::write_text_atomic_in(&self.fs, &self.path, &contents, self.options()).await
+    }
+
+    /// Load the value, modify it with `update` and save it, returning the new value.
+    pub async fn update<U>(&self, update: U) -> crate::Result<T>
+    where
+        U: FnOnce(&mut T),
+    {
+        let mut value = self.load().await?;
+        update(&mut value);
+        self.save(&value).await?;
+        Ok(value)
+    }
+}
+
+/// Whether `error` comes from a truncated or malformed file, rather than from JSON not matching the schema.
+fn is_corrupted(error: &crate::Error) -> bool {
+    matches!(error, crate::Error::SerdeJson(err) if err.is_syntax() || err.is_eof())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use futures::executor::block_on;
+    use serde_json::{json, Value};
+
+    use super::{upgrade, JsonFile, LoadOutcome, Migration};
+    use crate::{
+        fs::{system::FileSystem, BaseDirectory, FsOptions, InMemoryFs},
+        ErrorKind,
+    };
+
+    type Map = BTreeMap<String, u32>;
+
+    fn options() -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: Some(BaseDirectory::AppConfig),
+            ..Default::default()
+        })
+    }
+
+    fn migrations() -> Vec<Migration> {
+        vec![
+            Box::new(|mut value: Value| {
+                value["renamed"] = value["old"].take();
+                value.as_object_mut().unwrap().remove("old");
+                Ok(value)
+            }),
+            Box::new(|mut value: Value| {
+                value["added"] = true.into();
+                Ok(value)
+            }),
+        ]
+    }
+
+    #[test]
+    fn upgrade_legacy_file() {
+        let (data, version) = upgrade(json!({ "old": 1 }), &migrations()).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(data, json!({ "renamed": 1, "added": true }));
+    }
+
+    #[test]
+    fn upgrade_envelope() {
+        let (data, version) = upgrade(
+            json!({ "version": 1, "data": { "renamed": 2 } }),
+            &migrations(),
+        )
+        .unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(data, json!({ "renamed": 2, "added": true }));
+
+        let latest = json!({ "version": 2, "data": { "renamed": 2, "added": false } });
+        assert_eq!(
+            upgrade(latest, &migrations()).unwrap(),
+            (json!({ "renamed": 2, "added": false }), 2)
+        );
+    }
+
+    #[test]
+    fn newer_version() {
+        assert!(matches!(
+            upgrade(json!({ "version": 3, "data": {} }), &migrations()),
+            Err(crate::Error::UnsupportedVersion {
+                found: 3,
+                supported: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn load_and_save() {
+        let file =
+            JsonFile::<Map, _>::with_fs("a/b.json", BaseDirectory::AppConfig, InMemoryFs::new());
+        block_on(async {
+            assert_eq!(
+                file.load_with_outcome().await.unwrap(),
+                (Map::new(), LoadOutcome::Missing)
+            );
+            let value = file
+                .update(|map| {
+                    map.insert("a".into(), 1);
+                })
+                .await
+                .unwrap();
+            file.update(|map| {
+                map.insert("b".into(), 2);
+            })
+            .await
+            .unwrap();
+            // The backup holds the previous version.
+            file.fs()
+                .write_text_file("a/b.json", "{", options())
+                .await
+                .unwrap();
+            assert_eq!(
+                file.load_with_outcome().await.unwrap(),
+                (value, LoadOutcome::RestoredBackup)
+            );
+        });
+    }
+
+    #[test]
+    fn data_error() {
+        let file =
+            JsonFile::<Map, _>::with_fs("b.json", BaseDirectory::AppConfig, InMemoryFs::new());
+        block_on(async {
+            let contents = r#"{"version": 0, "data": {"a": "text"}}"#;
+            file.fs()
+                .write_text_file("b.json", contents, options())
+                .await
+                .unwrap();
+            let error = file.load_with_outcome().await.unwrap_err();
+            assert!(matches!(error, crate::Error::SerdeJson(ref err) if err.is_data()));
+            assert_eq!(
+                file.fs().read_text_file("b.json", options()).await.unwrap(),
+                contents
+            );
+            assert!(!file.fs().exists("b.json.bak", options()).await.unwrap());
+        });
+    }
+
+    #[test]
+    fn read_error() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            fs.write_text_file("b.json", "{\"a\": 1}", options())
+                .await
+                .unwrap();
+            fs.write_text_file("b.json.bak", "{\"a\": 0}", options())
+                .await
+                .unwrap();
+            fs.deny_reads("b.json", Some(BaseDirectory::AppConfig))
+                .unwrap();
+            let file = JsonFile::<Map, _>::with_fs("b.json", BaseDirectory::AppConfig, fs);
+            let error = file.load_with_outcome().await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+            let fs = file.fs();
+            // Only the reads of `b.json` are denied, not the ones of the renamed file.
+            fs.rename_file("b.json", "c.json", options()).await.unwrap();
+            assert_eq!(
+                fs.read_text_file("c.json", options()).await.unwrap(),
+                "{\"a\": 1}"
+            );
+            assert_eq!(
+                fs.read_text_file("b.json.bak", options()).await.unwrap(),
+                "{\"a\": 0}"
+            );
+        });
+    }
+}
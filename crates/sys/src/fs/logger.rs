@@ -0,0 +1,309 @@
+//! A [`log::Log`] implementation persisting the frontend logs, so support can collect them.
+//!
+//! The records are buffered and appended to `$APPLOG/frontend.log` (by default) with
+//! [`write_text_file`](super::write_text_file). When the file grows over the size limit,
+//! it is rotated to `frontend.log.1`, `frontend.log.2`, … with [`rename_file`](super::rename_file)
+//! and the oldest one is removed with [`remove_file`](super::remove_file).
+//! Tauri v1 can't tell the size of a file without reading it, so the file left by the previous launch
+//! is rotated by [`init`] too, and every launch starts a new file.
+//!
+//! Requires the `log` feature.
+//!
+//! ```rust,no_run
+//! use tauri_global_sys::fs::logger::{self, FileLoggerOptions};
+//!
+//! # async fn run() -> Result<(), tauri_global_sys::Error> {
+//! logger::init(FileLoggerOptions::default()).await?;
+//! log::info!("the frontend started");
+//! # Ok(())
+//! # }
+//! ```
+use std::{cell::RefCell, time::Duration};
+
+use log::{LevelFilter, Log, Metadata, Record};
+use wasm_bindgen_futures::spawn_local;
+
+use super::{BaseDirectory, FsDirOptions, FsOptions};
+use crate::timer::sleep;
+
+/// Options for [`init`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileLoggerOptions {
+    pub dir: BaseDirectory,
+    pub file_name: String,
+    pub level: LevelFilter,
+    /// The size over which the file is rotated, in bytes.
+    pub max_size: usize,
+    /// The number of files kept, including the current one.
+    pub max_files: usize,
+    /// The delay between a record and the flush of the buffer.
+    pub flush_interval: Duration,
+    /// The number of buffered records which triggers an immediate flush.
+    pub max_buffered: usize,
+}
+
+impl Default for FileLoggerOptions {
+    fn default() -> Self {
+        Self {
+            dir: BaseDirectory::AppLog,
+            file_name: "frontend.log".into(),
+            level: LevelFilter::Info,
+            max_size: 1024 * 1024,
+            max_files: 5,
+            flush_interval: Duration::from_secs(1),
+            max_buffered: 100,
+        }
+    }
+}
+
+impl FileLoggerOptions {
+    fn fs_options(&self, append: bool) -> Option<FsOptions> {
+        Some(FsOptions {
+            append: Some(append),
+            dir: Some(self.dir),
+        })
+    }
+
+    /// The path of the `index`-th file, the current one being the 0th.
+    fn file(&self, index: usize) -> String {
+        match index {
+            0 => self.file_name.clone(),
+            index => format!("{}.{index}", self.file_name),
+        }
+    }
+
+    /// The renames shifting every file by one, oldest first.
+    fn rotation(&self) -> Vec<(String, String)> {
+        (0..self.max_files.saturating_sub(1))
+            .rev()
+            .map(|index| (self.file(index), self.file(index + 1)))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    options: Option<FileLoggerOptions>,
+    buffer: String,
+    buffered: usize,
+    /// The size of the current file.
+    size: usize,
+    flushing: bool,
+    flush_scheduled: bool,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// When [`FileLogger::log`] flushes the buffer.
+enum Flush {
+    Now,
+    After(Duration),
+}
+
+/// The logger installed by [`init`].
+///
+/// Its state lives in a thread local, since the WebAssembly frontend is single threaded
+/// and [`log::Log`] must be [`Send`] and [`Sync`].
+struct FileLogger {
+    level: LevelFilter,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            String::from(js_sys::Date::new_0().to_iso_string()),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let flush_now = STATE.with_borrow_mut(|state| {
+            let Some(options) = &state.options else {
+                return None;
+            };
+            let (max_buffered, interval) = (options.max_buffered, options.flush_interval);
+            state.buffer.push_str(&line);
+            state.buffered += 1;
+            // A full buffer is flushed right away, even if a flush is already scheduled.
+            if state.buffered >= max_buffered {
+                return Some(Flush::Now);
+            }
+            if state.flush_scheduled {
+                return None;
+            }
+            state.flush_scheduled = true;
+            Some(Flush::After(interval))
+        });
+        match flush_now {
+            Some(Flush::Now) => spawn_local(flush_logs()),
+            Some(Flush::After(interval)) => spawn_local(async move {
+                sleep(interval).await;
+                flush_logs().await;
+            }),
+            None => {}
+        }
+    }
+
+    fn flush(&self) {
+        spawn_local(flush_logs());
+    }
+}
+
+async fn rotate(options: &FileLoggerOptions) -> crate::Result<()> {
+    if options.max_files <= 1 {
+        return super::remove_file(&options.file(0), options.fs_options(false)).await;
+    }
+    let oldest = options.file(options.max_files - 1);
+    if super::exists(&oldest, options.fs_options(false)).await? {
+        super::remove_file(&oldest, options.fs_options(false)).await?;
+    }
+    for (from, to) in options.rotation() {
+        if super::exists(&from, options.fs_options(false)).await? {
+            super::rename_file(&from, &to, options.fs_options(false)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Drop the oldest lines of `buffer` until it fits in `max_size` bytes.
+fn drop_oldest_lines(buffer: &mut String, max_size: usize) {
+    let Some(excess) = buffer
+        .len()
+        .checked_sub(max_size)
+        .filter(|excess| *excess > 0)
+    else {
+        return;
+    };
+    let end = buffer.as_bytes()[excess - 1..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(buffer.len(), |position| excess + position);
+    buffer.drain(..end);
+}
+
+/// Write the buffered records now.
+///
+/// If the write fails, the records are kept for the next flush, the oldest ones being dropped
+/// once they exceed [`FileLoggerOptions::max_size`].
+/// The errors are dropped: they can't be logged without looping back here.
+pub async fn flush_logs() {
+    let taken = STATE.with_borrow_mut(|state| {
+        state.flush_scheduled = false;
+        if state.flushing || state.buffer.is_empty() {
+            return None;
+        }
+        let options = state.options.clone()?;
+        state.flushing = true;
+        state.buffered = 0;
+        Some((options, std::mem::take(&mut state.buffer), state.size))
+    });
+    let Some((options, buffer, size)) = taken else {
+        return;
+    };
+    let mut size = size;
+    if size > 0 && size + buffer.len() > options.max_size && rotate(&options).await.is_ok() {
+        size = 0;
+    }
+    let written = super::write_text_file(&options.file(0), &buffer, options.fs_options(true)).await;
+    let reschedule = STATE.with_borrow_mut(|state| {
+        state.flushing = false;
+        match written {
+            Ok(()) => state.size = size + buffer.len(),
+            // Keep the records for the flush scheduled by the next record,
+            // without growing over `max_size` if the file stays unwritable.
+            Err(_) => {
+                state.buffer.insert_str(0, &buffer);
+                drop_oldest_lines(&mut state.buffer, options.max_size);
+                return false;
+            }
+        }
+        // Records logged during the flush found it busy.
+        !state.buffer.is_empty() && !state.flush_scheduled
+    });
+    if reschedule {
+        STATE.with_borrow_mut(|state| state.flush_scheduled = true);
+        sleep(options.flush_interval).await;
+        Box::pin(flush_logs()).await;
+    }
+}
+
+/// The log files, newest first, relative to [`FileLoggerOptions::dir`].
+pub fn log_files() -> Vec<String> {
+    STATE.with_borrow(|state| {
+        state.options.as_ref().map_or_else(Vec::new, |options| {
+            (0..options.max_files.max(1))
+                .map(|index| options.file(index))
+                .collect()
+        })
+    })
+}
+
+/// Create the log directory, rotate the file of the previous launch and install the file logger as the global logger.
+///
+/// Fails with [`Error::Logger`](crate::Error::Logger) if a global logger is already set.
+pub async fn init(options: FileLoggerOptions) -> crate::Result<()> {
+    let dir_options = FsDirOptions {
+        dir: Some(options.dir),
+        recursive: Some(true),
+    };
+    super::create_dir("", Some(dir_options)).await?;
+    if super::exists(&options.file(0), options.fs_options(false)).await? {
+        rotate(&options).await?;
+    }
+    let level = options.level;
+    log::set_logger(Box::leak(Box::new(FileLogger { level })))
+        .map_err(|err| crate::Error::Logger(err.to_string()))?;
+    log::set_max_level(level);
+    STATE.with_borrow_mut(|state| {
+        state.options = Some(options);
+        state.size = 0;
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drop_oldest_lines, FileLoggerOptions};
+
+    #[test]
+    fn capped_buffer() {
+        let mut buffer = "first\nsecond\nthird\n".to_string();
+        drop_oldest_lines(&mut buffer, 100);
+        assert_eq!(buffer, "first\nsecond\nthird\n");
+        drop_oldest_lines(&mut buffer, 13);
+        assert_eq!(buffer, "second\nthird\n");
+        drop_oldest_lines(&mut buffer, 12);
+        assert_eq!(buffer, "third\n");
+        drop_oldest_lines(&mut buffer, 3);
+        assert_eq!(buffer, "");
+    }
+
+    #[test]
+    fn rotation() {
+        let options = FileLoggerOptions {
+            max_files: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            options.rotation(),
+            vec![
+                ("frontend.log.1".to_string(), "frontend.log.2".to_string()),
+                ("frontend.log".to_string(), "frontend.log.1".to_string()),
+            ]
+        );
+        let single = FileLoggerOptions {
+            max_files: 1,
+            ..Default::default()
+        };
+        assert!(single.rotation().is_empty());
+    }
+}
@@ -0,0 +1,589 @@
+//! An in-memory [`FileSystem`], for the tests of the code using the `fs` module.
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use futures::{future::LocalBoxFuture, FutureExt};
+
+use super::{
+    dir::{check_relative_path, join_relative},
+    system::FileSystem,
+    BaseDirectory, FileEntry, FsDirOptions, FsOptions,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Dir,
+    File(Vec<u8>),
+}
+
+type Key = (Option<BaseDirectory>, String);
+
+/// A [`FileSystem`] kept in memory.
+///
+/// It enforces the same rules as Tauri: the paths must be relative and without `..`,
+/// a directory can only be created in an existing one and removed when empty
+/// unless the `recursive` option is set, and so on.
+/// The failures are [`Error::Tauri`](crate::Error::Tauri) with the same messages as on Linux,
+/// so [`Error::kind`](crate::Error::kind) works on them.
+///
+/// Every [`BaseDirectory`] (and the current directory, for [`None`]) is a distinct root which always exists.
+/// The [`FileEntry::path`] of the listed entries starts with the [variable](BaseDirectory::variable)
+/// of their base directory, like `$APPDATA/databases/main.db`.
+///
+/// Failures can be injected with [`InMemoryFs::deny_reads`].
+#[derive(Debug, Default)]
+pub struct InMemoryFs {
+    nodes: RefCell<BTreeMap<Key, Node>>,
+    denied_reads: RefCell<BTreeSet<Key>>,
+}
+
+fn os_error(path: &str, message: &str) -> crate::Error {
+    crate::Error::tauri(format!("path: {path}: {message}"))
+}
+
+fn not_found(path: &str) -> crate::Error {
+    os_error(path, "No such file or directory (os error 2)")
+}
+
+fn already_exists(path: &str) -> crate::Error {
+    os_error(path, "File exists (os error 17)")
+}
+
+fn permission_denied(path: &str) -> crate::Error {
+    os_error(path, "Permission denied (os error 13)")
+}
+
+fn not_a_directory(path: &str) -> crate::Error {
+    os_error(path, "Not a directory (os error 20)")
+}
+
+fn is_a_directory(path: &str) -> crate::Error {
+    os_error(path, "Is a directory (os error 21)")
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn is_inside(path: &str, dir: &str) -> bool {
+    dir.is_empty() && !path.is_empty()
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+impl InMemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make the reads of the `path` file or directory fail with a permission denied error,
+    /// whether it exists or not.
+    pub fn deny_reads(&self, path: &str, dir: Option<BaseDirectory>) -> crate::Result<()> {
+        self.denied_reads.borrow_mut().insert(Self::key(path, dir)?);
+        Ok(())
+    }
+
+    fn check_readable(&self, key: &Key) -> crate::Result<()> {
+        if self.denied_reads.borrow().contains(key) {
+            return Err(permission_denied(&Self::display_path(key)));
+        }
+        Ok(())
+    }
+
+    fn key(path: &str, dir: Option<BaseDirectory>) -> crate::Result<Key> {
+        check_relative_path(path)?;
+        Ok((dir, join_relative("", path)))
+    }
+
+    fn display_path((dir, path): &Key) -> String {
+        match dir {
+            Some(dir) => join_relative(dir.variable(), path),
+            None => path.clone(),
+        }
+    }
+
+    fn node(&self, key: &Key) -> Option<Node> {
+        if key.1.is_empty() {
+            return Some(Node::Dir);
+        }
+        self.nodes.borrow().get(key).cloned()
+    }
+
+    /// Check that the parent of `key` is an existing directory.
+    fn check_parent(&self, key: &Key) -> crate::Result<()> {
+        let parent = (key.0, parent(&key.1).to_string());
+        match self.node(&parent) {
+            Some(Node::Dir) => Ok(()),
+            Some(Node::File(_)) => Err(not_a_directory(&Self::display_path(key))),
+            None => Err(not_found(&Self::display_path(key))),
+        }
+    }
+
+    fn read(&self, path: &str, options: Option<FsOptions>) -> crate::Result<Vec<u8>> {
+        let key = Self::key(path, options.and_then(|options| options.dir))?;
+        self.check_readable(&key)?;
+        match self.node(&key) {
+            Some(Node::File(contents)) => Ok(contents),
+            Some(Node::Dir) => Err(is_a_directory(&Self::display_path(&key))),
+            None => Err(not_found(&Self::display_path(&key))),
+        }
+    }
+
+    fn write(&self, path: &str, contents: &[u8], options: Option<FsOptions>) -> crate::Result<()> {
+        let options = options.unwrap_or_default();
+        let key = Self::key(path, options.dir)?;
+        self.check_parent(&key)?;
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes.get_mut(&key) {
+            Some(Node::Dir) => Err(is_a_directory(&Self::display_path(&key))),
+            Some(Node::File(existing)) if options.append == Some(true) => {
+                existing.extend_from_slice(contents);
+                Ok(())
+            }
+            _ => {
+                nodes.insert(key, Node::File(contents.to_vec()));
+                Ok(())
+            }
+        }
+    }
+
+    fn entries(&self, key: &Key, recursive: bool) -> Vec<FileEntry> {
+        let children = self
+            .nodes
+            .borrow()
+            .iter()
+            .filter(|((dir, path), _)| *dir == key.0 && parent(path) == key.1 && !path.is_empty())
+            .map(|(key, node)| (key.clone(), node.clone()))
+            .collect::<Vec<_>>();
+        children
+            .into_iter()
+            .map(|(child, node)| FileEntry {
+                children: match node {
+                    Node::Dir if recursive => Some(self.entries(&child, true)),
+                    Node::Dir => Some(Vec::new()),
+                    Node::File(_) => None,
+                },
+                name: child.1.rsplit('/').next().map(String::from),
+                path: Self::display_path(&child),
+            })
+            .collect()
+    }
+
+    fn has_children(&self, key: &Key) -> bool {
+        self.nodes
+            .borrow()
+            .keys()
+            .any(|(base, path)| *base == key.0 && is_inside(path, &key.1))
+    }
+
+    fn sync_create_dir(&self, dir: &str, options: Option<FsDirOptions>) -> crate::Result<()> {
+        let options = options.unwrap_or_default();
+        let key = Self::key(dir, options.dir)?;
+        if options.recursive == Some(true) {
+            let mut path = String::new();
+            for component in key.1.split('/').filter(|component| !component.is_empty()) {
+                path = join_relative(&path, component);
+                let ancestor = (key.0, path.clone());
+                match self.node(&ancestor) {
+                    Some(Node::Dir) => {}
+                    Some(Node::File(_)) => {
+                        return Err(already_exists(&Self::display_path(&ancestor)))
+                    }
+                    None => {
+                        self.nodes.borrow_mut().insert(ancestor, Node::Dir);
+                    }
+                }
+            }
+            return Ok(());
+        }
+        if self.node(&key).is_some() {
+            return Err(already_exists(&Self::display_path(&key)));
+        }
+        self.check_parent(&key)?;
+        self.nodes.borrow_mut().insert(key, Node::Dir);
+        Ok(())
+    }
+
+    fn sync_remove_dir(&self, dir: &str, options: Option<FsDirOptions>) -> crate::Result<()> {
+        let options = options.unwrap_or_default();
+        let key = Self::key(dir, options.dir)?;
+        match self.node(&key) {
+            Some(Node::Dir) => {}
+            Some(Node::File(_)) => return Err(not_a_directory(&Self::display_path(&key))),
+            None => return Err(not_found(&Self::display_path(&key))),
+        }
+        if self.has_children(&key) && options.recursive != Some(true) {
+            return Err(os_error(
+                &Self::display_path(&key),
+                "Directory not empty (os error 39)",
+            ));
+        }
+        self.nodes.borrow_mut().retain(|(base, path), _| {
+            *base != key.0 || (path != &key.1 && !is_inside(path, &key.1))
+        });
+        Ok(())
+    }
+
+    fn sync_remove_file(&self, file: &str, options: Option<FsOptions>) -> crate::Result<()> {
+        let key = Self::key(file, options.and_then(|options| options.dir))?;
+        match self.node(&key) {
+            Some(Node::File(_)) => {
+                self.nodes.borrow_mut().remove(&key);
+                Ok(())
+            }
+            Some(Node::Dir) => Err(is_a_directory(&Self::display_path(&key))),
+            None => Err(not_found(&Self::display_path(&key))),
+        }
+    }
+
+    fn sync_rename(
+        &self,
+        old_path: &str,
+        new_path: &str,
+        options: Option<FsOptions>,
+    ) -> crate::Result<()> {
+        let dir = options.and_then(|options| options.dir);
+        let from = Self::key(old_path, dir)?;
+        let to = Self::key(new_path, dir)?;
+        let node = self
+            .node(&from)
+            .ok_or_else(|| not_found(&Self::display_path(&from)))?;
+        if from == to {
+            return Ok(());
+        }
+        self.check_parent(&to)?;
+        match (&node, self.node(&to)) {
+            (Node::File(_), Some(Node::Dir)) => {
+                return Err(is_a_directory(&Self::display_path(&to)))
+            }
+            (Node::Dir, Some(Node::File(_))) => {
+                return Err(not_a_directory(&Self::display_path(&to)))
+            }
+            (Node::Dir, _) if to.1 == from.1 || is_inside(&to.1, &from.1) => {
+                return Err(os_error(
+                    &Self::display_path(&to),
+                    "Invalid argument (os error 22)",
+                ))
+            }
+            // A directory only replaces an empty one.
+            (Node::Dir, Some(Node::Dir)) if self.has_children(&to) => {
+                return Err(os_error(
+                    &Self::display_path(&to),
+                    "Directory not empty (os error 39)",
+                ))
+            }
+            _ => {}
+        }
+        let mut nodes = self.nodes.borrow_mut();
+        let moved = nodes
+            .keys()
+            .filter(|(base, path)| *base == from.0 && (path == &from.1 || is_inside(path, &from.1)))
+            .cloned()
+            .collect::<Vec<_>>();
+        nodes.remove(&to);
+        for key in moved {
+            let node = nodes.remove(&key).expect("listed above");
+            let path = format!("{}{}", to.1, &key.1[from.1.len()..]);
+            nodes.insert((to.0, path), node);
+        }
+        Ok(())
+    }
+}
+
+impl FileSystem for InMemoryFs {
+    fn copy_file<'a>(
+        &'a self,
+        source: &'a str,
+        destination: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move {
+            let contents = self.read(source, options.clone())?;
+            let options = options.map(|options| FsOptions {
+                append: None,
+                ..options
+            });
+            self.write(destination, &contents, options)
+        }
+        .boxed_local()
+    }
+
+    fn create_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move { self.sync_create_dir(dir, options) }.boxed_local()
+    }
+
+    fn exists<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<bool>> {
+        async move {
+            let key = Self::key(path, options.and_then(|options| options.dir))?;
+            Ok(self.node(&key).is_some())
+        }
+        .boxed_local()
+    }
+
+    fn read_binary_file<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<Vec<u8>>> {
+        async move { self.read(path, options) }.boxed_local()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<Vec<FileEntry>>> {
+        async move {
+            let options = options.unwrap_or_default();
+            let key = Self::key(dir, options.dir)?;
+            self.check_readable(&key)?;
+            match self.node(&key) {
+                Some(Node::Dir) => Ok(self.entries(&key, options.recursive == Some(true))),
+                Some(Node::File(_)) => Err(not_a_directory(&Self::display_path(&key))),
+                None => Err(not_found(&Self::display_path(&key))),
+            }
+        }
+        .boxed_local()
+    }
+
+    fn read_text_file<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<String>> {
+        async move {
+            String::from_utf8(self.read(path, options)?)
+                .map_err(|_| crate::Error::tauri("stream did not contain valid UTF-8".to_string()))
+        }
+        .boxed_local()
+    }
+
+    fn remove_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move { self.sync_remove_dir(dir, options) }.boxed_local()
+    }
+
+    fn remove_file<'a>(
+        &'a self,
+        file: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move { self.sync_remove_file(file, options) }.boxed_local()
+    }
+
+    fn rename_file<'a>(
+        &'a self,
+        old_path: &'a str,
+        new_path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move { self.sync_rename(old_path, new_path, options) }.boxed_local()
+    }
+
+    fn write_binary_file<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a [u8],
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move { self.write(path, contents, options) }.boxed_local()
+    }
+
+    fn write_text_file<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        async move { self.write(path, contents.as_bytes(), options) }.boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::InMemoryFs;
+    use crate::{
+        fs::{system::FileSystem, BaseDirectory, FileEntry, FsDirOptions, FsOptions},
+        ErrorKind,
+    };
+
+    fn options() -> Option<FsOptions> {
+        Some(FsOptions {
+            dir: Some(BaseDirectory::AppData),
+            ..Default::default()
+        })
+    }
+
+    fn dir_options(recursive: bool) -> Option<FsDirOptions> {
+        Some(FsDirOptions {
+            dir: Some(BaseDirectory::AppData),
+            recursive: Some(recursive),
+        })
+    }
+
+    #[test]
+    fn files() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            fs.write_text_file("a.txt", "hello", options())
+                .await
+                .unwrap();
+            let append = Some(FsOptions {
+                append: Some(true),
+                dir: Some(BaseDirectory::AppData),
+            });
+            fs.write_text_file("a.txt", " world", append).await.unwrap();
+            assert_eq!(
+                fs.read_text_file("a.txt", options()).await.unwrap(),
+                "hello world"
+            );
+            // Each base directory is a distinct root.
+            assert!(!fs.exists("a.txt", None).await.unwrap());
+            fs.copy_file("a.txt", "b.txt", options()).await.unwrap();
+            fs.rename_file("b.txt", "c.txt", options()).await.unwrap();
+            assert!(!fs.exists("b.txt", options()).await.unwrap());
+            assert_eq!(
+                fs.read_binary_file("c.txt", options()).await.unwrap(),
+                b"hello world"
+            );
+            fs.remove_file("c.txt", options()).await.unwrap();
+            let error = fs.read_text_file("c.txt", options()).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::NotFound);
+        });
+    }
+
+    #[test]
+    fn denied_reads() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            fs.create_dir("a", dir_options(false)).await.unwrap();
+            fs.write_text_file("a/b.txt", "b", options()).await.unwrap();
+            fs.deny_reads("a/b.txt", Some(BaseDirectory::AppData))
+                .unwrap();
+            fs.deny_reads("a", Some(BaseDirectory::AppData)).unwrap();
+            let error = fs.read_text_file("a/b.txt", options()).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+            let error = fs.read_dir("a", dir_options(false)).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+            // Only the reads fail.
+            assert!(fs.exists("a/b.txt", options()).await.unwrap());
+            fs.write_text_file("a/b.txt", "c", options()).await.unwrap();
+            assert!(fs.read_text_file("a/b.txt", None).await.is_err());
+        });
+    }
+
+    #[test]
+    fn path_rules() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            assert!(matches!(
+                fs.write_text_file("/etc/passwd", "", None).await,
+                Err(crate::Error::InvalidPath { .. })
+            ));
+            assert!(matches!(
+                fs.read_text_file("a/../../b", options()).await,
+                Err(crate::Error::InvalidPath { .. })
+            ));
+            let error = fs.write_text_file("missing/a.txt", "", options()).await;
+            assert_eq!(error.unwrap_err().kind(), ErrorKind::NotFound);
+        });
+    }
+
+    #[test]
+    fn directories() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            let error = fs.create_dir("a/b", dir_options(false)).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::NotFound);
+            fs.create_dir("a/b", dir_options(true)).await.unwrap();
+            let error = fs.create_dir("a", dir_options(false)).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+            fs.write_text_file("a/b/c.txt", "c", options())
+                .await
+                .unwrap();
+
+            assert_eq!(
+                fs.read_dir("a", dir_options(false)).await.unwrap(),
+                vec![FileEntry {
+                    children: Some(vec![]),
+                    name: Some("b".into()),
+                    path: "$APPDATA/a/b".into(),
+                }]
+            );
+            assert_eq!(
+                fs.read_dir("", dir_options(true)).await.unwrap(),
+                vec![FileEntry {
+                    children: Some(vec![FileEntry {
+                        children: Some(vec![FileEntry {
+                            children: None,
+                            name: Some("c.txt".into()),
+                            path: "$APPDATA/a/b/c.txt".into(),
+                        }]),
+                        name: Some("b".into()),
+                        path: "$APPDATA/a/b".into(),
+                    }]),
+                    name: Some("a".into()),
+                    path: "$APPDATA/a".into(),
+                }]
+            );
+
+            let error = fs.remove_dir("a", dir_options(false)).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::DirectoryNotEmpty);
+            let error = fs
+                .remove_dir("a/b/c.txt", dir_options(false))
+                .await
+                .unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::NotADirectory);
+            fs.rename_file("a", "moved", options()).await.unwrap();
+            assert!(fs.exists("moved/b/c.txt", options()).await.unwrap());
+            fs.remove_dir("moved", dir_options(true)).await.unwrap();
+            assert!(fs.read_dir("", dir_options(true)).await.unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn renames() {
+        let fs = InMemoryFs::new();
+        block_on(async {
+            fs.write_text_file("a.txt", "a", options()).await.unwrap();
+            fs.rename_file("a.txt", "a.txt", options()).await.unwrap();
+            fs.rename_file("./a.txt", "a.txt", options()).await.unwrap();
+            assert_eq!(fs.read_text_file("a.txt", options()).await.unwrap(), "a");
+
+            fs.create_dir("src/inner", dir_options(true)).await.unwrap();
+            fs.create_dir("full", dir_options(false)).await.unwrap();
+            fs.write_text_file("full/keep.txt", "keep", options())
+                .await
+                .unwrap();
+            let error = fs.rename_file("src", "full", options()).await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::DirectoryNotEmpty);
+            assert!(fs.exists("src/inner", options()).await.unwrap());
+            assert!(!fs.exists("full/inner", options()).await.unwrap());
+
+            fs.create_dir("empty", dir_options(false)).await.unwrap();
+            fs.rename_file("src", "empty", options()).await.unwrap();
+            assert!(fs.exists("empty/inner", options()).await.unwrap());
+            assert!(!fs.exists("src", options()).await.unwrap());
+            fs.remove_dir("empty", dir_options(true)).await.unwrap();
+            fs.remove_dir("full", dir_options(true)).await.unwrap();
+            fs.remove_file("a.txt", options()).await.unwrap();
+            assert!(fs.read_dir("", dir_options(true)).await.unwrap().is_empty());
+        });
+    }
+}
@@ -0,0 +1,342 @@
+//! Local validation of the `tauri.allowlist.fs.scope` globs.
+//!
+//! Tauri rejects the paths outside of the scope with an opaque promise error.
+//! [`FsScope`] predicts that rejection from the same configuration,
+//! and can be [installed](set_global_scope) so that every `fs` function checks its paths
+//! before reaching the IPC, failing with [`Error::ScopeDenied`](crate::Error::ScopeDenied)
+//! when the denial is certain, see [`FsScope::check_certain`].
+//!
+//! ```rust
+//! use tauri_global_sys::fs::{scope::FsScope, BaseDirectory};
+//!
+//! let scope = FsScope::from_json(r#"["$APPDATA/databases/*"]"#).unwrap();
+//! assert!(scope.check("databases/main.db", Some(BaseDirectory::AppData)).is_ok());
+//! assert!(scope.check("settings.json", Some(BaseDirectory::AppData)).is_err());
+//! ```
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use serde::Deserialize;
+
+use super::{glob::GlobPattern, BaseDirectory};
+
+/// The scope, as written in tauri.conf.json.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ScopeConfig {
+    Allow(Vec<String>),
+    Scope {
+        #[serde(default)]
+        allow: Vec<String>,
+        #[serde(default)]
+        deny: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: String,
+    symbolic: GlobPattern,
+    /// The pattern with its variable replaced by the resolved directory, if known.
+    resolved: Option<GlobPattern>,
+}
+
+impl Rule {
+    fn new(pattern: String) -> crate::Result<Self> {
+        Ok(Self {
+            symbolic: GlobPattern::new(&pattern)?,
+            pattern,
+            resolved: None,
+        })
+    }
+
+    /// Whether the directory of the pattern is known, which it is without a variable.
+    fn is_resolved(&self) -> bool {
+        !self.pattern.starts_with('$') || self.resolved.is_some()
+    }
+
+    fn matches(&self, candidates: &[String]) -> bool {
+        candidates.iter().any(|candidate| {
+            self.symbolic.matches(candidate)
+                || self
+                    .resolved
+                    .as_ref()
+                    .is_some_and(|resolved| resolved.matches(candidate))
+        })
+    }
+}
+
+/// Lexically resolve the `.` and `..` components and use `/` as separator.
+fn normalize(path: &str) -> String {
+    let absolute = path.starts_with(['/', '\\']);
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    let path = components.join("/");
+    if absolute {
+        format!("/{path}")
+    } else {
+        path
+    }
+}
+
+fn is_absolute(path: &str) -> bool {
+    let mut chars = path.chars();
+    path.starts_with(['/', '\\'])
+        || matches!((chars.next(), chars.next()), (Some(drive), Some(':')) if drive.is_ascii_alphabetic())
+}
+
+/// The allowed and denied path globs of the fs scope.
+///
+/// A path is allowed if it matches one of the allowed patterns and none of the denied ones.
+///
+/// The paths relative to a [`BaseDirectory`] are matched as `$VARIABLE/path`.
+/// Absolute paths can only match the patterns whose variable was [resolved](FsScope::set_variable),
+/// and a path under a directory only known through another variable (say `$APPDATA` inside `$HOME`)
+/// is predicted as denied.
+#[derive(Debug, Clone, Default)]
+pub struct FsScope {
+    allow: Vec<Rule>,
+    deny: Vec<Rule>,
+    variables: HashMap<BaseDirectory, String>,
+}
+
+impl FsScope {
+    pub fn new<A, D>(allow: A, deny: D) -> crate::Result<Self>
+    where
+        A: IntoIterator,
+        A::Item: Into<String>,
+        D: IntoIterator,
+        D::Item: Into<String>,
+    {
+        Ok(Self {
+            allow: allow
+                .into_iter()
+                .map(|pattern| Rule::new(pattern.into()))
+                .collect::<crate::Result<_>>()?,
+            deny: deny
+                .into_iter()
+                .map(|pattern| Rule::new(pattern.into()))
+                .collect::<crate::Result<_>>()?,
+            variables: HashMap::new(),
+        })
+    }
+
+    /// Parse the `tauri.allowlist.fs.scope` value: either an array of allowed patterns
+    /// or an object with `allow` and `deny` arrays.
+    pub fn from_json(scope: &str) -> crate::Result<Self> {
+        match serde_json::from_str(scope)? {
+            ScopeConfig::Allow(allow) => Self::new(allow, Vec::<String>::new()),
+            ScopeConfig::Scope { allow, deny } => Self::new(allow, deny),
+        }
+    }
+
+    /// Set the resolved path of a [`BaseDirectory`] variable,
+    /// so the patterns using it also match absolute paths.
+    pub fn set_variable(&mut self, base_dir: BaseDirectory, resolved: &str) -> crate::Result<()> {
+        let variable = base_dir.variable();
+        let resolved = resolved.trim_end_matches(['/', '\\']);
+        for rule in self.allow.iter_mut().chain(self.deny.iter_mut()) {
+            let Some(rest) = rule.pattern.strip_prefix(variable) else {
+                continue;
+            };
+            // `$APP` must not match the start of `$APPDATA`.
+            if rest.is_empty() || rest.starts_with(['/', '\\']) {
+                rule.resolved = Some(GlobPattern::new(&format!("{resolved}{rest}"))?);
+            }
+        }
+        self.variables.insert(base_dir, resolved.to_string());
+        Ok(())
+    }
+
+    /// The allowed patterns.
+    pub fn allowed(&self) -> impl Iterator<Item = &str> {
+        self.allow.iter().map(|rule| rule.pattern.as_str())
+    }
+
+    /// The denied patterns.
+    pub fn denied(&self) -> impl Iterator<Item = &str> {
+        self.deny.iter().map(|rule| rule.pattern.as_str())
+    }
+
+    /// The forms of `path` matched against the patterns.
+    fn candidates(&self, path: &str, base_dir: Option<BaseDirectory>) -> Vec<String> {
+        match base_dir {
+            Some(base_dir) if !is_absolute(path) => {
+                let mut candidates = vec![normalize(&format!("{}/{path}", base_dir.variable()))];
+                if let Some(resolved) = self.variables.get(&base_dir) {
+                    candidates.push(normalize(&format!("{resolved}/{path}")));
+                }
+                candidates
+            }
+            _ => vec![normalize(path)],
+        }
+    }
+
+    /// Predict whether Tauri allows access to `path`, relative to `base_dir`.
+    pub fn is_allowed(&self, path: &str, base_dir: Option<BaseDirectory>) -> bool {
+        self.check(path, base_dir).is_ok()
+    }
+
+    /// Predict whether Tauri allows access to `path`, relative to `base_dir`.
+    ///
+    /// The returned [`Error::ScopeDenied`](crate::Error::ScopeDenied) holds the matching denied patterns,
+    /// or all the allowed ones if none matched.
+    pub fn check(&self, path: &str, base_dir: Option<BaseDirectory>) -> crate::Result<()> {
+        let candidates = self.candidates(path, base_dir);
+        let denied_by = self.denied_by(&candidates);
+        let denied = |patterns| {
+            Err(crate::Error::ScopeDenied {
+                path: candidates[0].clone(),
+                patterns,
+            })
+        };
+        if !denied_by.is_empty() {
+            return denied(denied_by);
+        }
+        if !self.allow.iter().any(|rule| rule.matches(&candidates)) {
+            return denied(self.allowed().map(String::from).collect());
+        }
+        Ok(())
+    }
+
+    /// The denied patterns matching one of the `candidates`.
+    fn denied_by(&self, candidates: &[String]) -> Vec<String> {
+        self.deny
+            .iter()
+            .filter(|rule| rule.matches(candidates))
+            .map(|rule| rule.pattern.clone())
+            .collect()
+    }
+
+    /// Same as [`FsScope::check`], but only fails when the denial is certain:
+    /// a denied pattern matches, or no allowed pattern does while all their variables and the one of `base_dir`
+    /// are [resolved](FsScope::set_variable).
+    ///
+    /// Otherwise an allowed pattern could still match through another variable, so the path is let through.
+    pub fn check_certain(&self, path: &str, base_dir: Option<BaseDirectory>) -> crate::Result<()> {
+        let result = self.check(path, base_dir);
+        if result.is_err() && !self.is_certain(path, base_dir) {
+            let candidates = self.candidates(path, base_dir);
+            if self.denied_by(&candidates).is_empty() {
+                #[cfg(feature = "log")]
+                log::debug!("{path} may be outside of the fs scope, letting Tauri decide");
+                return Ok(());
+            }
+        }
+        result
+    }
+
+    /// Whether every path form needed to match the allowed patterns is known.
+    fn is_certain(&self, path: &str, base_dir: Option<BaseDirectory>) -> bool {
+        let path_resolved = match base_dir {
+            Some(base_dir) if !is_absolute(path) => self.variables.contains_key(&base_dir),
+            _ => true,
+        };
+        path_resolved && self.allow.iter().all(Rule::is_resolved)
+    }
+}
+
+thread_local! {
+    static GLOBAL_SCOPE: RefCell<Option<Rc<FsScope>>> = const { RefCell::new(None) };
+}
+
+/// Install a scope checked by every `fs` function before calling Tauri, or remove it with [`None`].
+///
+/// Only the certain denials fail, see [`FsScope::check_certain`].
+pub fn set_global_scope(scope: Option<FsScope>) {
+    GLOBAL_SCOPE.with_borrow_mut(|global| *global = scope.map(Rc::new));
+}
+
+/// The scope installed with [`set_global_scope`].
+pub fn global_scope() -> Option<Rc<FsScope>> {
+    GLOBAL_SCOPE.with_borrow(Clone::clone)
+}
+
+/// Check `path` against the global scope, if any, failing only on a certain denial.
+pub(crate) fn pre_check(path: &str, base_dir: Option<BaseDirectory>) -> crate::Result<()> {
+    match global_scope() {
+        Some(scope) => scope.check_certain(path, base_dir),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, FsScope};
+    use crate::fs::BaseDirectory;
+
+    #[test]
+    fn normalized() {
+        assert_eq!(normalize("$APPDATA/a/./b/../c"), "$APPDATA/a/c");
+        assert_eq!(normalize("/home\\user//x"), "/home/user/x");
+        assert_eq!(normalize("$APPDATA/../etc"), "etc");
+    }
+
+    #[test]
+    fn allow_and_deny() {
+        let scope = FsScope::from_json(
+            r#"{"allow": ["$APPDATA/**", "$DOWNLOAD/*.zip"], "deny": ["$APPDATA/secrets/**"]}"#,
+        )
+        .unwrap();
+        assert!(scope.is_allowed("db/main.db", Some(BaseDirectory::AppData)));
+        assert!(scope.is_allowed("a.zip", Some(BaseDirectory::Download)));
+        assert!(!scope.is_allowed("sub/a.zip", Some(BaseDirectory::Download)));
+        assert!(!scope.is_allowed("../escape", Some(BaseDirectory::AppData)));
+        assert!(!scope.is_allowed("/etc/passwd", Some(BaseDirectory::AppData)));
+        match scope.check("secrets/key", Some(BaseDirectory::AppData)) {
+            Err(crate::Error::ScopeDenied { path, patterns }) => {
+                assert_eq!(path, "$APPDATA/secrets/key");
+                assert_eq!(patterns, ["$APPDATA/secrets/**"]);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resolved_variables() {
+        let mut scope = FsScope::from_json(r#"["$APP/*", "$APPDATA/databases/*"]"#).unwrap();
+        assert!(!scope.is_allowed("/home/me/.local/share/app/databases/a.db", None));
+        scope
+            .set_variable(BaseDirectory::AppData, "/home/me/.local/share/app/")
+            .unwrap();
+        assert!(scope.is_allowed("/home/me/.local/share/app/databases/a.db", None));
+        assert!(scope.is_allowed(
+            "/home/me/.local/share/app/databases/a.db",
+            Some(BaseDirectory::Home)
+        ));
+        assert!(!scope.is_allowed("/home/me/.local/share/app/a.db", None));
+    }
+
+    #[test]
+    fn certain_denials() {
+        let mut scope = FsScope::from_json(
+            r#"{"allow": ["$APPDATA/**", "$HOME/notes/*"], "deny": ["$APPDATA/secrets/**"]}"#,
+        )
+        .unwrap();
+        // `$APPDATA` may be inside `$HOME/notes`, while the deny pattern matches for sure.
+        assert!(scope
+            .check("../other", Some(BaseDirectory::AppData))
+            .is_err());
+        assert!(scope
+            .check_certain("../other", Some(BaseDirectory::AppData))
+            .is_ok());
+        assert!(scope
+            .check_certain("secrets/key", Some(BaseDirectory::AppData))
+            .is_err());
+
+        scope
+            .set_variable(BaseDirectory::AppData, "/home/me/.local/share/app")
+            .unwrap();
+        assert!(scope.check_certain("/etc/passwd", None).is_ok());
+        scope.set_variable(BaseDirectory::Home, "/home/me").unwrap();
+        assert!(scope.check_certain("/etc/passwd", None).is_err());
+        assert!(scope.check_certain("/home/me/notes/a.md", None).is_ok());
+    }
+}
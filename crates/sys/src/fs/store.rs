@@ -0,0 +1,564 @@
+//! A persistent key-value store, like `tauri-plugin-store` but without a backend plugin.
+//!
+//! The values are kept in memory and saved as a [`JsonFile`] under a [`BaseDirectory`].
+//! Every change is broadcast to the other windows with [`event::emit`](crate::event::emit),
+//! so all the stores opened on the same file share the same view.
+//!
+//! Each window saves its own changes. The saves are [atomic](super::atomic) with a temporary file per write,
+//! so concurrent saves from several windows never mix: the file holds the last complete save.
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    channel::mpsc,
+    future::{AbortHandle, Abortable, LocalBoxFuture},
+    FutureExt, Stream, StreamExt,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+
+use super::{
+    json::JsonFile,
+    system::{FileSystem, TauriFs},
+    BaseDirectory,
+};
+use crate::{event, timer::sleep};
+
+/// The delay of the auto-save after the last change.
+pub const DEFAULT_SAVE_DELAY: Duration = Duration::from_millis(100);
+
+/// A change of a [`KvStore`] entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreChange {
+    pub key: String,
+    /// The new value, [`None`] if the entry was deleted.
+    pub value: Option<Value>,
+    /// Whether the change was made by another window.
+    pub remote: bool,
+}
+
+/// The payload of the change events.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct ChangeMessage {
+    origin: String,
+    key: String,
+    value: Option<Value>,
+}
+
+/// The event on which the changes of the store saved in `path` are broadcast.
+///
+/// Tauri only accepts alphanumeric characters, `-`, `/`, `:` and `_` in event names,
+/// so the path is hex encoded, which keeps distinct paths on distinct events.
+fn change_event(dir: BaseDirectory, path: &str) -> String {
+    let path = path.bytes().map(|b| format!("{b:02x}")).collect::<String>();
+    format!("tauri-global-sys://kv-store/{}/{path}", dir as u8)
+}
+
+type Values = BTreeMap<String, Value>;
+
+/// The side effects of a store: the Tauri events, the tasks and the timers outside of the tests.
+#[derive(Clone)]
+struct Runtime {
+    emit: Rc<dyn Fn(String, ChangeMessage) -> LocalBoxFuture<'static, crate::Result<()>>>,
+    spawn: Rc<dyn Fn(LocalBoxFuture<'static, ()>)>,
+    sleep: Rc<dyn Fn(Duration) -> LocalBoxFuture<'static, ()>>,
+}
+
+impl Runtime {
+    fn tauri() -> Self {
+        Self {
+            emit: Rc::new(|event, message| {
+                async move { event::emit(&event, &message).await }.boxed_local()
+            }),
+            spawn: Rc::new(spawn_local),
+            sleep: Rc::new(|delay| sleep(delay).boxed_local()),
+        }
+    }
+}
+
+struct Inner<F: FileSystem + 'static> {
+    file: Rc<JsonFile<Values, F>>,
+    event: String,
+    origin: String,
+    runtime: Runtime,
+    values: RefCell<Values>,
+    save_delay: RefCell<Option<Duration>>,
+    pending_save: RefCell<Option<AbortHandle>>,
+    subscribers: RefCell<Vec<mpsc::UnboundedSender<StoreChange>>>,
+    remote_changes: AbortHandle,
+}
+
+impl<F: FileSystem + 'static> Drop for Inner<F> {
+    fn drop(&mut self) {
+        self.remote_changes.abort();
+        // Don't lose the changes waiting for the auto-save.
+        if let Some(pending) = self.pending_save.take() {
+            pending.abort();
+            let file = self.file.clone();
+            let values = self.values.take();
+            (self.runtime.spawn)(
+                async move {
+                    if let Err(_err) = file.save(&values).await {
+                        #[cfg(feature = "log")]
+                        log::error!("Cannot save {}: {_err}", file.path());
+                    }
+                }
+                .boxed_local(),
+            );
+        }
+    }
+}
+
+impl<F: FileSystem + 'static> Inner<F> {
+    fn notify(&self, change: StoreChange) {
+        self.subscribers
+            .borrow_mut()
+            .retain(|subscriber| subscriber.unbounded_send(change.clone()).is_ok());
+    }
+}
+
+/// A persistent key-value store.
+///
+/// Cloning a store gives another handle to the same values.
+///
+/// ```rust,no_run
+/// use tauri_global_sys::fs::{store::KvStore, BaseDirectory};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let store = KvStore::open("store.json", BaseDirectory::AppData).await?;
+/// store.set("volume", &0.8).await?;
+/// let volume = store.get::<f64>("volume")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct KvStore<F: FileSystem + 'static = TauriFs> {
+    inner: Rc<Inner<F>>,
+}
+
+impl<F: FileSystem + 'static> Clone for KvStore<F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl KvStore {
+    /// Load the store saved in `path`, and start following the changes made by the other windows.
+    pub async fn open(path: &str, dir: BaseDirectory) -> crate::Result<Self> {
+        Self::open_with_fs(path, dir, TauriFs).await
+    }
+}
+
+impl<F: FileSystem + 'static> KvStore<F> {
+    /// [`KvStore::open`] through `fs`, the changes are still broadcast with the Tauri events.
+    pub async fn open_with_fs(path: &str, dir: BaseDirectory, fs: F) -> crate::Result<Self> {
+        let event = change_event(dir, path);
+        let messages = event::listen::<ChangeMessage>(&event)
+            .await?
+            .map(|message| message.payload);
+        let file = JsonFile::with_fs(path, dir, fs);
+        Self::start(file, event, messages, Runtime::tauri()).await
+    }
+
+    /// Load the values from `file` and apply the remote `messages`.
+    async fn start(
+        file: JsonFile<Values, F>,
+        event: String,
+        mut messages: impl Stream<Item = ChangeMessage> + Unpin + 'static,
+        runtime: Runtime,
+    ) -> crate::Result<Self> {
+        let values = file.load().await?;
+        let (remote_changes, registration) = AbortHandle::new_pair();
+        let inner = Rc::new(Inner {
+            file: Rc::new(file),
+            event,
+            origin: Uuid::new_v4().to_string(),
+            runtime: runtime.clone(),
+            values: RefCell::new(values),
+            save_delay: RefCell::new(Some(DEFAULT_SAVE_DELAY)),
+            pending_save: RefCell::new(None),
+            subscribers: RefCell::new(Vec::new()),
+            remote_changes,
+        });
+        let weak = Rc::downgrade(&inner);
+        let task = async move {
+            while let Some(message) = messages.next().await {
+                let Some(inner) = weak.upgrade() else {
+                    break;
+                };
+                let ChangeMessage { origin, key, value } = message;
+                if origin == inner.origin {
+                    continue;
+                }
+                // The window which made the change saves it.
+                {
+                    let mut values = inner.values.borrow_mut();
+                    match &value {
+                        Some(value) => values.insert(key.clone(), value.clone()),
+                        None => values.remove(&key),
+                    };
+                }
+                inner.notify(StoreChange {
+                    key,
+                    value,
+                    remote: true,
+                });
+            }
+        };
+        (runtime.spawn)(Abortable::new(task, registration).map(|_| ()).boxed_local());
+        Ok(Self { inner })
+    }
+
+    /// Set the delay of the auto-save after the last change, or disable it with [`None`].
+    pub fn set_save_delay(&self, delay: Option<Duration>) {
+        *self.inner.save_delay.borrow_mut() = delay;
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> crate::Result<Option<T>> {
+        self.inner
+            .values
+            .borrow()
+            .get(key)
+            .map(|value| T::deserialize(value))
+            .transpose()
+            .map_err(crate::Error::from)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.inner.values.borrow().contains_key(key)
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.inner.values.borrow().keys().cloned().collect()
+    }
+
+    pub fn entries(&self) -> Vec<(String, Value)> {
+        self.inner
+            .values
+            .borrow()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.values.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.values.borrow().is_empty()
+    }
+
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> crate::Result<()> {
+        let value = serde_json::to_value(value)?;
+        self.inner
+            .values
+            .borrow_mut()
+            .insert(key.to_string(), value.clone());
+        self.changed(key, Some(value)).await
+    }
+
+    /// Delete the `key` entry, returning whether it existed.
+    pub async fn delete(&self, key: &str) -> crate::Result<bool> {
+        if self.inner.values.borrow_mut().remove(key).is_none() {
+            return Ok(false);
+        }
+        self.changed(key, None).await?;
+        Ok(true)
+    }
+
+    async fn changed(&self, key: &str, value: Option<Value>) -> crate::Result<()> {
+        self.inner.notify(StoreChange {
+            key: key.to_string(),
+            value: value.clone(),
+            remote: false,
+        });
+        self.schedule_save();
+        let message = ChangeMessage {
+            origin: self.inner.origin.clone(),
+            key: key.to_string(),
+            value,
+        };
+        (self.inner.runtime.emit)(self.inner.event.clone(), message).await
+    }
+
+    /// Save after the save delay, unless another change comes first.
+    fn schedule_save(&self) {
+        let Some(delay) = *self.inner.save_delay.borrow() else {
+            return;
+        };
+        let (handle, registration) = AbortHandle::new_pair();
+        if let Some(previous) = self.inner.pending_save.replace(Some(handle)) {
+            previous.abort();
+        }
+        let weak = Rc::downgrade(&self.inner);
+        let sleep = (self.inner.runtime.sleep)(delay);
+        let task = async move {
+            sleep.await;
+            if let Some(inner) = weak.upgrade() {
+                inner.pending_save.take();
+                if let Err(_err) = save(&inner).await {
+                    #[cfg(feature = "log")]
+                    log::error!("Cannot save {}: {_err}", inner.file.path());
+                }
+            }
+        };
+        (self.inner.runtime.spawn)(Abortable::new(task, registration).map(|_| ()).boxed_local());
+    }
+
+    /// Save now, cancelling the pending auto-save.
+    pub async fn save(&self) -> crate::Result<()> {
+        if let Some(pending) = self.inner.pending_save.take() {
+            pending.abort();
+        }
+        save(&self.inner).await
+    }
+
+    /// A stream of the changes, made by this window or by the other ones.
+    pub fn changes(&self) -> StoreChanges {
+        let (sender, receiver) = mpsc::unbounded();
+        self.inner.subscribers.borrow_mut().push(sender);
+        StoreChanges { receiver }
+    }
+}
+
+async fn save<F: FileSystem + 'static>(inner: &Inner<F>) -> crate::Result<()> {
+    let values = inner.values.borrow().clone();
+    inner.file.save(&values).await
+}
+
+/// A [`Stream`] of [`StoreChange`], created with [`KvStore::changes`].
+///
+/// It ends when every handle to the store is dropped.
+pub struct StoreChanges {
+    receiver: mpsc::UnboundedReceiver<StoreChange>,
+}
+
+impl Stream for StoreChanges {
+    type Item = StoreChange;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use futures::{
+        channel::mpsc,
+        executor::{block_on, LocalPool},
+        future,
+        task::LocalSpawnExt,
+        FutureExt, StreamExt,
+    };
+    use serde_json::json;
+
+    use super::{change_event, ChangeMessage, KvStore, Runtime, StoreChange, Values};
+    use crate::fs::{json::JsonFile, system::FileSystem, BaseDirectory, FsOptions, InMemoryFs};
+
+    /// A store on an [`InMemoryFs`], whose tasks run on a [`LocalPool`] and whose timers are immediate.
+    struct Harness {
+        // Dropped before the pool, which runs its save-on-drop.
+        store: KvStore<InMemoryFs>,
+        pool: LocalPool,
+        emitted: Rc<RefCell<Vec<ChangeMessage>>>,
+        remote: mpsc::UnboundedSender<ChangeMessage>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let mut pool = LocalPool::new();
+            let spawner = pool.spawner();
+            let emitted = Rc::new(RefCell::new(Vec::new()));
+            let runtime = Runtime {
+                emit: Rc::new({
+                    let emitted = emitted.clone();
+                    move |_, message| {
+                        emitted.borrow_mut().push(message);
+                        future::ready(Ok(())).boxed_local()
+                    }
+                }),
+                spawn: Rc::new(move |task| spawner.spawn_local(task).unwrap()),
+                sleep: Rc::new(|_| future::ready(()).boxed_local()),
+            };
+            let (remote, messages) = mpsc::unbounded();
+            let file = JsonFile::with_fs("store.json", BaseDirectory::AppData, InMemoryFs::new());
+            let event = change_event(BaseDirectory::AppData, "store.json");
+            let store = pool
+                .run_until(KvStore::start(file, event, messages, runtime))
+                .unwrap();
+            Self {
+                store,
+                pool,
+                emitted,
+                remote,
+            }
+        }
+
+        fn saved(&self, path: &str) -> Option<Values> {
+            let file = &self.store.inner.file;
+            let options = Some(FsOptions {
+                dir: Some(BaseDirectory::AppData),
+                ..Default::default()
+            });
+            let contents = block_on(file.fs().read_text_file(path, options)).ok()?;
+            let saved = serde_json::from_str::<serde_json::Value>(&contents).unwrap();
+            Some(serde_json::from_value(saved["data"].clone()).unwrap())
+        }
+    }
+
+    fn values(entries: &[(&str, serde_json::Value)]) -> Values {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn get_set_delete() {
+        let mut harness = Harness::new();
+        let store = harness.store.clone();
+        let mut changes = store.changes();
+        harness.pool.run_until(async {
+            store.set("volume", &0.5).await.unwrap();
+            store.set("theme", &"dark").await.unwrap();
+            assert!(store.delete("theme").await.unwrap());
+            assert!(!store.delete("theme").await.unwrap());
+        });
+        assert_eq!(store.get::<f64>("volume").unwrap(), Some(0.5));
+        assert_eq!(store.get::<String>("theme").unwrap(), None);
+        assert!(store.get::<String>("volume").is_err());
+        assert!(store.contains_key("volume"));
+        assert_eq!(store.keys(), ["volume"]);
+        assert_eq!(store.len(), 1);
+
+        let emitted = harness.emitted.borrow();
+        assert_eq!(emitted.len(), 3);
+        assert!(emitted
+            .iter()
+            .all(|message| message.origin == store.inner.origin));
+        assert_eq!(emitted[2].value, None);
+        let change = block_on(changes.next()).unwrap();
+        assert_eq!(
+            change,
+            StoreChange {
+                key: "volume".into(),
+                value: Some(json!(0.5)),
+                remote: false
+            }
+        );
+    }
+
+    #[test]
+    fn debounced_save() {
+        let mut harness = Harness::new();
+        let store = harness.store.clone();
+        harness.pool.run_until(async {
+            store.set("a", &1).await.unwrap();
+            store.set("b", &2).await.unwrap();
+        });
+        assert_eq!(harness.saved("store.json"), None);
+        harness.pool.run_until_stalled();
+        assert_eq!(
+            harness.saved("store.json"),
+            Some(values(&[("a", json!(1)), ("b", json!(2))]))
+        );
+        // The first auto-save was cancelled by the second change: there was no previous version to back up.
+        assert_eq!(harness.saved("store.json.bak"), None);
+
+        store.set_save_delay(None);
+        harness
+            .pool
+            .run_until(async { store.set("c", &3).await.unwrap() });
+        harness.pool.run_until_stalled();
+        assert_eq!(harness.saved("store.json").unwrap().len(), 2);
+        block_on(store.save()).unwrap();
+        assert_eq!(harness.saved("store.json").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn remote_changes() {
+        let mut harness = Harness::new();
+        let store = harness.store.clone();
+        let mut changes = store.changes();
+        let message = |origin: &str, key: &str, value| ChangeMessage {
+            origin: origin.into(),
+            key: key.into(),
+            value,
+        };
+        harness
+            .remote
+            .unbounded_send(message("other", "a", Some(json!(1))))
+            .unwrap();
+        // The echo of a change made by this store is ignored.
+        let origin = store.inner.origin.clone();
+        harness
+            .remote
+            .unbounded_send(message(&origin, "b", Some(json!(2))))
+            .unwrap();
+        harness.pool.run_until_stalled();
+        assert_eq!(store.get::<u32>("a").unwrap(), Some(1));
+        assert!(!store.contains_key("b"));
+        assert_eq!(
+            block_on(changes.next()).unwrap(),
+            StoreChange {
+                key: "a".into(),
+                value: Some(json!(1)),
+                remote: true
+            }
+        );
+        // The window which made the change saves it.
+        assert_eq!(harness.saved("store.json"), None);
+        assert!(harness.emitted.borrow().is_empty());
+    }
+
+    #[test]
+    fn save_on_drop() {
+        let Harness {
+            mut pool, store, ..
+        } = Harness::new();
+        let file = store.inner.file.clone();
+        pool.run_until(async { store.set("a", &1).await.unwrap() });
+        drop(store);
+        pool.run_until_stalled();
+        assert_eq!(block_on(file.load()).unwrap(), values(&[("a", json!(1))]));
+    }
+
+    #[test]
+    fn event_name() {
+        assert_eq!(
+            change_event(BaseDirectory::AppData, "a/b.json"),
+            format!(
+                "tauri-global-sys://kv-store/{}/612f622e6a736f6e",
+                BaseDirectory::AppData as u8
+            )
+        );
+        assert_ne!(
+            change_event(BaseDirectory::AppData, "a.json"),
+            change_event(BaseDirectory::AppData, "a_json")
+        );
+    }
+
+    #[test]
+    fn message_json() {
+        let message = ChangeMessage {
+            origin: "a".into(),
+            key: "volume".into(),
+            value: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"origin":"a","key":"volume","value":null}"#
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! A [`FileSystem`] trait mirroring the `fs` functions,
+//! so the code using it can be tested with an [`InMemoryFs`](super::memory::InMemoryFs).
+//!
+//! The atomic writes (the `_in` functions of [`atomic`](super::atomic)), [`walk_dir_in`](super::walk::walk_dir_in),
+//! [`JsonFile::with_fs`](super::json::JsonFile::with_fs) and [`KvStore::open_with_fs`](super::store::KvStore::open_with_fs)
+//! accept one. The other helpers of the `fs` module still call the Tauri API directly.
+use futures::{future::LocalBoxFuture, FutureExt};
+
+use super::{FileEntry, FsDirOptions, FsOptions};
+
+/// The operations of the `fs` module.
+///
+/// ```rust
+/// use tauri_global_sys::fs::{system::FileSystem, BaseDirectory, FsOptions};
+///
+/// async fn load_theme(fs: &impl FileSystem) -> String {
+///     let options = FsOptions {
+///         dir: Some(BaseDirectory::AppConfig),
+///         ..Default::default()
+///     };
+///     fs.read_text_file("theme.txt", Some(options))
+///         .await
+///         .unwrap_or_else(|_| "light".into())
+/// }
+/// ```
+pub trait FileSystem {
+    fn copy_file<'a>(
+        &'a self,
+        source: &'a str,
+        destination: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+
+    fn create_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+
+    fn exists<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<bool>>;
+
+    fn read_binary_file<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<Vec<u8>>>;
+
+    fn read_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<Vec<FileEntry>>>;
+
+    fn read_text_file<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<String>>;
+
+    fn remove_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+
+    fn remove_file<'a>(
+        &'a self,
+        file: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+
+    fn rename_file<'a>(
+        &'a self,
+        old_path: &'a str,
+        new_path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+
+    fn write_binary_file<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a [u8],
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+
+    fn write_text_file<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>>;
+}
+
+/// The [`FileSystem`] backed by the Tauri `fs` module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TauriFs;
+
+impl FileSystem for TauriFs {
+    fn copy_file<'a>(
+        &'a self,
+        source: &'a str,
+        destination: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::copy_file(source, destination, options).boxed_local()
+    }
+
+    fn create_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::create_dir(dir, options).boxed_local()
+    }
+
+    fn exists<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<bool>> {
+        super::exists(path, options).boxed_local()
+    }
+
+    fn read_binary_file<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<Vec<u8>>> {
+        super::read_binary_file(path, options).boxed_local()
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<Vec<FileEntry>>> {
+        super::read_dir(dir, options).boxed_local()
+    }
+
+    fn read_text_file<'a>(
+        &'a self,
+        path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<String>> {
+        super::read_text_file(path, options).boxed_local()
+    }
+
+    fn remove_dir<'a>(
+        &'a self,
+        dir: &'a str,
+        options: Option<FsDirOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::remove_dir(dir, options).boxed_local()
+    }
+
+    fn remove_file<'a>(
+        &'a self,
+        file: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::remove_file(file, options).boxed_local()
+    }
+
+    fn rename_file<'a>(
+        &'a self,
+        old_path: &'a str,
+        new_path: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::rename_file(old_path, new_path, options).boxed_local()
+    }
+
+    fn write_binary_file<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a [u8],
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::write_binary_file(path, contents, options).boxed_local()
+    }
+
+    fn write_text_file<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a str,
+        options: Option<FsOptions>,
+    ) -> LocalBoxFuture<'a, crate::Result<()>> {
+        super::write_text_file(path, contents, options).boxed_local()
+    }
+}
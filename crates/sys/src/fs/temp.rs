@@ -0,0 +1,222 @@
+//! Scratch files and directories in [`BaseDirectory::Temp`], removed when dropped.
+//!
+//! ```rust,no_run
+//! use tauri_global_sys::fs::temp::TempFile;
+//!
+//! # async fn run() -> Result<(), tauri_global_sys::Error> {
+//! let export = TempFile::with_suffix(".csv").await?;
+//! export.write_text("id,name\n").await?;
+//! println!("exported to {}", export.path());
+//! // Removed here, unless `export.persist()` was called.
+//! # Ok(())
+//! # }
+//! ```
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+
+use super::{dir::check_relative_path, BaseDirectory, FsDirOptions, FsOptions};
+
+fn unique_name(prefix: &str, suffix: &str) -> String {
+    format!("{prefix}{}{suffix}", Uuid::new_v4().simple())
+}
+
+/// Check that `suffix` keeps the name a single `tmp-<uuid>` entry of [`BaseDirectory::Temp`].
+fn check_suffix(suffix: &str) -> crate::Result<()> {
+    if suffix.contains(['/', '\\']) || suffix.contains("..") {
+        return Err(crate::Error::InvalidPath {
+            path: suffix.to_string(),
+            reason: "a temporary file suffix can't contain separators or `..`",
+        });
+    }
+    Ok(())
+}
+
+fn temp_options() -> Option<FsOptions> {
+    Some(FsOptions {
+        dir: Some(BaseDirectory::Temp),
+        ..Default::default()
+    })
+}
+
+async fn absolute(name: &str) -> crate::Result<String> {
+    crate::path::join(vec![crate::os::tempdir().await?, name.into()]).await
+}
+
+/// A file with a unique name in [`BaseDirectory::Temp`], removed on drop.
+#[derive(Debug)]
+pub struct TempFile {
+    name: String,
+    path: String,
+    keep: bool,
+}
+
+impl TempFile {
+    /// Create an empty temporary file.
+    pub async fn new() -> crate::Result<Self> {
+        Self::with_suffix("").await
+    }
+
+    /// Create an empty temporary file whose name ends with `suffix`, e.g. an extension.
+    ///
+    /// Fails with [`Error::InvalidPath`](crate::Error::InvalidPath) if `suffix` contains a separator or `..`.
+    pub async fn with_suffix(suffix: &str) -> crate::Result<Self> {
+        check_suffix(suffix)?;
+        let name = unique_name("tmp-", suffix);
+        super::write_binary_file(&name, b"", temp_options()).await?;
+        // Removed on drop if its path can't be resolved.
+        let mut file = Self {
+            name,
+            path: String::new(),
+            keep: false,
+        };
+        file.path = absolute(&file.name).await?;
+        Ok(file)
+    }
+
+    /// The name, relative to [`BaseDirectory::Temp`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The absolute path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub async fn write_text(&self, contents: &str) -> crate::Result<()> {
+        super::write_text_file(&self.name, contents, temp_options()).await
+    }
+
+    pub async fn write_binary(&self, contents: impl AsRef<[u8]>) -> crate::Result<()> {
+        super::write_binary_file(&self.name, contents, temp_options()).await
+    }
+
+    pub async fn read_text(&self) -> crate::Result<String> {
+        super::read_text_file(&self.name, temp_options()).await
+    }
+
+    pub async fn read_binary(&self) -> crate::Result<Vec<u8>> {
+        super::read_binary_file(&self.name, temp_options()).await
+    }
+
+    /// Keep the file and return its absolute path.
+    pub fn persist(mut self) -> String {
+        self.keep = true;
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        let name = std::mem::take(&mut self.name);
+        spawn_local(async move {
+            let _re = super::remove_file(&name, temp_options()).await;
+            #[cfg(feature = "log")]
+            if let Err(err) = _re {
+                log::error!("Error on removing the temporary file {name:?} ({err})");
+            }
+        });
+    }
+}
+
+/// A directory with a unique name in [`BaseDirectory::Temp`], removed with its contents on drop.
+#[derive(Debug)]
+pub struct TempDir {
+    name: String,
+    path: String,
+    keep: bool,
+}
+
+impl TempDir {
+    pub async fn new() -> crate::Result<Self> {
+        let name = unique_name("tmp-", "");
+        super::create_dir(
+            &name,
+            Some(FsDirOptions {
+                dir: Some(BaseDirectory::Temp),
+                recursive: Some(true),
+            }),
+        )
+        .await?;
+        // Removed on drop if its path can't be resolved.
+        let mut dir = Self {
+            name,
+            path: String::new(),
+            keep: false,
+        };
+        dir.path = absolute(&dir.name).await?;
+        Ok(dir)
+    }
+
+    /// The name, relative to [`BaseDirectory::Temp`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The absolute path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The path of `relative` inside the directory, relative to [`BaseDirectory::Temp`].
+    ///
+    /// Fails with [`Error::InvalidPath`](crate::Error::InvalidPath) if `relative` is absolute or contains `..`.
+    pub fn child(&self, relative: &str) -> crate::Result<String> {
+        check_relative_path(relative)?;
+        Ok(super::walk::join(&self.name, relative))
+    }
+
+    /// Keep the directory and return its absolute path.
+    pub fn persist(mut self) -> String {
+        self.keep = true;
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        let name = std::mem::take(&mut self.name);
+        spawn_local(async move {
+            let options = FsDirOptions {
+                dir: Some(BaseDirectory::Temp),
+                recursive: Some(true),
+            };
+            let _re = super::remove_dir(&name, Some(options)).await;
+            #[cfg(feature = "log")]
+            if let Err(err) = _re {
+                log::error!("Error on removing the temporary directory {name:?} ({err})");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_suffix, unique_name};
+
+    #[test]
+    fn unique_names() {
+        let first = unique_name("tmp-", ".csv");
+        assert!(first.starts_with("tmp-") && first.ends_with(".csv"));
+        assert_eq!(first.len(), "tmp-".len() + 32 + ".csv".len());
+        assert_ne!(first, unique_name("tmp-", ".csv"));
+    }
+
+    #[test]
+    fn suffixes() {
+        assert!(check_suffix("").is_ok());
+        assert!(check_suffix(".tar.gz").is_ok());
+        for suffix in ["/../x", "\\x", "/x", "..", "x..y"] {
+            assert!(
+                matches!(check_suffix(suffix), Err(crate::Error::InvalidPath { .. })),
+                "{suffix}"
+            );
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures::{future::LocalBoxFuture, stream::FuturesUnordered, FutureExt, Stream, StreamExt};
+
+use super::{
+    glob::GlobPattern,
+    system::{FileSystem, TauriFs},
+    BaseDirectory, FileEntry, FsDirOptions,
+};
+
+/// Which kind of entries [`walk_dir`] yields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EntryFilter {
+    #[default]
+    All,
+    FilesOnly,
+    DirsOnly,
+}
+
+/// Options for [`walk_dir`].
+#[derive(Debug, Clone)]
+pub struct WalkDirOptions {
+    pub dir: Option<BaseDirectory>,
+    /// The maximum depth to walk, the entries of the root directory are at depth 1.
+    ///
+    /// [`None`] means no limit.
+    pub max_depth: Option<usize>,
+    /// If not empty, only the entries whose relative path matches one of these patterns are yielded.
+    ///
+    /// Directories are still walked even if they don't match.
+    pub include: Vec<GlobPattern>,
+    /// The entries whose relative path matches one of these patterns are skipped.
+    ///
+    /// Excluded directories are not walked.
+    pub exclude: Vec<GlobPattern>,
+    pub filter: EntryFilter,
+    /// The maximum number of directories read at the same time.
+    pub concurrency: usize,
+}
+
+impl Default for WalkDirOptions {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_depth: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            filter: EntryFilter::All,
+            concurrency: 4,
+        }
+    }
+}
+
+impl WalkDirOptions {
+    fn is_excluded(&self, relative_path: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| pattern.matches(relative_path))
+    }
+
+    fn is_yielded(&self, relative_path: &str, is_dir: bool) -> bool {
+        let kind = match self.filter {
+            EntryFilter::All => true,
+            EntryFilter::FilesOnly => !is_dir,
+            EntryFilter::DirsOnly => is_dir,
+        };
+        kind && (self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern.matches(relative_path)))
+    }
+}
+
+/// An entry yielded by [`walk_dir`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WalkEntry {
+    /// The entry, without its children.
+    pub entry: FileEntry,
+    /// The path of the entry, as passed to the fs functions: the walked root joined with [`WalkEntry::relative_path`].
+    pub path: String,
+    /// The path of the entry relative to the walked root, always separated with `/`.
+    pub relative_path: String,
+    /// The depth of the entry, the entries of the walked root are at depth 1.
+    pub depth: usize,
+}
+
+impl WalkEntry {
+    pub fn is_dir(&self) -> bool {
+        self.entry.children.is_some()
+    }
+}
+
+/// A directory to read: its path, its path relative to the root and its depth.
+type Pending = (String, String, usize);
+
+type ReadDir = LocalBoxFuture<'static, (Pending, crate::Result<Vec<FileEntry>>)>;
+
+pub(crate) fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{name}", dir.trim_end_matches(['/', '\\']))
+    }
+}
+
+/// A [`Stream`] walking a directory tree, created with [`walk_dir`] or [`walk_dir_in`].
+pub struct WalkDir<F = TauriFs> {
+    fs: Rc<F>,
+    options: WalkDirOptions,
+    queue: VecDeque<Pending>,
+    reading: FuturesUnordered<ReadDir>,
+    ready: VecDeque<crate::Result<WalkEntry>>,
+}
+
+impl<F: FileSystem + 'static> WalkDir<F> {
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
+    fn read(&self, pending: Pending) -> ReadDir {
+        let options = Some(FsDirOptions {
+            dir: self.options.dir,
+            recursive: Some(false),
+        });
+        let fs = self.fs.clone();
+        async move {
+            let entries = fs.read_dir(&pending.0, options).await;
+            (pending, entries)
+        }
+        .boxed_local()
+    }
+
+    fn push_entries(&mut self, (dir, relative_dir, depth): Pending, entries: Vec<FileEntry>) {
+        let depth = depth + 1;
+        for entry in entries {
+            let Some(name) = entry.name.clone() else {
+                continue;
+            };
+            let path = join(&dir, &name);
+            let relative_path = join(&relative_dir, &name);
+            if self.options.is_excluded(&relative_path) {
+                continue;
+            }
+            let is_dir = entry.children.is_some();
+            if is_dir && self.options.max_depth.is_none_or(|max| depth < max) {
+                self.queue
+                    .push_back((path.clone(), relative_path.clone(), depth));
+            }
+            if self.options.is_yielded(&relative_path, is_dir) {
+                self.ready.push_back(Ok(WalkEntry {
+                    entry,
+                    path,
+                    relative_path,
+                    depth,
+                }));
+            }
+        }
+    }
+}
+
+impl<F: FileSystem + 'static> Stream for WalkDir<F> {
+    type Item = crate::Result<WalkEntry>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Poll::Ready(Some(item));
+            }
+            while self.reading.len() < self.options.concurrency.max(1) {
+                let Some(pending) = self.queue.pop_front() else {
+                    break;
+                };
+                let read = self.read(pending);
+                self.reading.push(read);
+            }
+            match self.reading.poll_next_unpin(cx) {
+                Poll::Ready(Some((pending, Ok(entries)))) => self.push_entries(pending, entries),
+                Poll::Ready(Some((_, Err(error)))) => self.ready.push_back(Err(error)),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Walk the `root` directory tree lazily, level by level.
+///
+/// Unlike [`read_dir`](super::read_dir) with `recursive: true`,
+/// the tree is never fully materialized: each directory is read when the walk reaches it.
+/// A directory that can't be read yields an error and the walk goes on.
+///
+/// The include and exclude patterns are matched against the path relative to `root`.
+pub fn walk_dir(root: &str, options: WalkDirOptions) -> WalkDir {
+    walk_dir_in(TauriFs, root, options)
+}
+
+/// [`walk_dir`] through `fs`.
+pub fn walk_dir_in<F: FileSystem + 'static>(
+    fs: F,
+    root: &str,
+    options: WalkDirOptions,
+) -> WalkDir<F> {
+    let mut queue = VecDeque::new();
+    if options.max_depth != Some(0) {
+        queue.push_back((root.to_string(), String::new(), 0));
+    }
+    WalkDir {
+        fs: Rc::new(fs),
+        options,
+        queue,
+        reading: FuturesUnordered::new(),
+        ready: VecDeque::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, StreamExt};
+
+    use super::{join, walk_dir_in, EntryFilter, WalkDir, WalkDirOptions};
+    use crate::{
+        fs::{
+            glob::GlobPattern, system::FileSystem, BaseDirectory, FsDirOptions, FsOptions,
+            InMemoryFs,
+        },
+        ErrorKind,
+    };
+
+    fn options() -> WalkDirOptions {
+        WalkDirOptions {
+            dir: Some(BaseDirectory::AppData),
+            ..Default::default()
+        }
+    }
+
+    /// An [`InMemoryFs`] holding `paths`, the ones ending with `/` being directories.
+    fn tree(paths: &[&str]) -> InMemoryFs {
+        let fs = InMemoryFs::new();
+        let dir_options = Some(FsDirOptions {
+            dir: Some(BaseDirectory::AppData),
+            recursive: Some(true),
+        });
+        let file_options = || {
+            Some(FsOptions {
+                dir: Some(BaseDirectory::AppData),
+                ..Default::default()
+            })
+        };
+        block_on(async {
+            for path in paths {
+                match path.strip_suffix('/') {
+                    Some(dir) => fs.create_dir(dir, dir_options.clone()).await.unwrap(),
+                    None => fs.write_text_file(path, "", file_options()).await.unwrap(),
+                }
+            }
+        });
+        fs
+    }
+
+    /// The relative path and depth of the walked entries, the errors being kept as is.
+    fn walked(walk: WalkDir<InMemoryFs>) -> Vec<crate::Result<(String, usize)>> {
+        block_on(
+            walk.map(|entry| entry.map(|entry| (entry.relative_path, entry.depth)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn join_paths() {
+        assert_eq!(join("", "a.txt"), "a.txt");
+        assert_eq!(join("/home/user/", "a.txt"), "/home/user/a.txt");
+        assert_eq!(join("logs", "a.txt"), "logs/a.txt");
+    }
+
+    #[test]
+    fn filters() {
+        let options = WalkDirOptions {
+            include: vec![GlobPattern::new("**/*.json").unwrap()],
+            exclude: vec![GlobPattern::new("**/node_modules").unwrap()],
+            filter: EntryFilter::FilesOnly,
+            ..Default::default()
+        };
+        assert!(options.is_excluded("a/node_modules"));
+        assert!(!options.is_excluded("a/node_modules.json"));
+        assert!(options.is_yielded("a/b/config.json", false));
+        assert!(!options.is_yielded("a/b/config.json", true));
+        assert!(!options.is_yielded("a/b/config.toml", false));
+    }
+
+    #[test]
+    fn breadth_first() {
+        let fs = tree(&["a/b/", "a/b/c.txt", "a/d.txt", "e.txt"]);
+        let walk = walk_dir_in(fs, "", options());
+        assert_eq!(
+            walked(walk)
+                .into_iter()
+                .map(Result::unwrap)
+                .collect::<Vec<_>>(),
+            [
+                ("a", 1),
+                ("e.txt", 1),
+                ("a/b", 2),
+                ("a/d.txt", 2),
+                ("a/b/c.txt", 3)
+            ]
+            .map(|(path, depth)| (path.to_string(), depth))
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        let paths = ["a/b/", "a/b/c.txt", "a/d.txt", "e.txt"];
+        let depths = |max_depth| {
+            let options = WalkDirOptions {
+                max_depth: Some(max_depth),
+                ..options()
+            };
+            walked(walk_dir_in(tree(&paths), "", options))
+                .into_iter()
+                .map(|entry| entry.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        assert!(depths(0).is_empty());
+        assert_eq!(depths(1), ["a", "e.txt"]);
+        assert_eq!(depths(2), ["a", "e.txt", "a/b", "a/d.txt"]);
+    }
+
+    #[test]
+    fn concurrency() {
+        let fs = tree(&["a/", "a/1", "b/", "b/1", "c/", "c/1", "d/", "d/1"]);
+        let options = WalkDirOptions {
+            concurrency: 2,
+            ..options()
+        };
+        let mut walk = walk_dir_in(fs, "", options);
+        block_on(async {
+            for _ in 0..4 {
+                assert!(walk.next().await.unwrap().unwrap().is_dir());
+            }
+            assert_eq!(walk.queue.len(), 4);
+            // Only two directories are read at the same time.
+            assert_eq!(walk.next().await.unwrap().unwrap().relative_path, "a/1");
+            assert_eq!(walk.queue.len(), 2);
+            assert!(walk.reading.len() <= 1);
+        });
+    }
+
+    #[test]
+    fn read_errors() {
+        let fs = tree(&["a/", "a/1", "b/", "b/1"]);
+        fs.deny_reads("a", Some(BaseDirectory::AppData)).unwrap();
+        let walked = walked(walk_dir_in(fs, "", options()));
+        assert_eq!(walked.len(), 4);
+        assert_eq!(
+            walked[2].as_ref().unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        // The walk goes on after the error.
+        assert_eq!(walked[3].as_ref().unwrap(), &("b/1".to_string(), 2));
+    }
+}
@@ -0,0 +1,431 @@
+//! A polling file and directory watcher.
+//!
+//! Tauri v1 has no fs watch API, so [`watch`] takes a snapshot of the watched path
+//! with [`read_dir`](super::read_dir) and [`exists`](super::exists) at every interval
+//! and diffs it against the previous one. The first snapshot is the baseline: no events are reported before it.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{future::LocalBoxFuture, FutureExt, Stream};
+
+use super::{BaseDirectory, FileEntry, FsDirOptions, FsOptions};
+use crate::{timer::sleep, ErrorKind};
+
+/// A change noticed by [`watch`].
+///
+/// The paths are the ones returned by [`read_dir`](super::read_dir), so they are absolute.
+/// A watched file is resolved to the same form, with [`BaseDirectory::resolve`] and [`resolve`](crate::path::resolve).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WatchEvent {
+    Created {
+        path: String,
+        is_dir: bool,
+    },
+    Removed {
+        path: String,
+        is_dir: bool,
+    },
+    /// A removed entry and a created one that look alike:
+    /// they have the same content hash or the same name.
+    ///
+    /// This is only a guess, the polling can't tell a rename from a removal followed by a creation.
+    Renamed {
+        from: String,
+        to: String,
+        is_dir: bool,
+    },
+    /// The content hash of a file changed, only emitted with [`Watch::hash_small_files`].
+    Modified {
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SnapshotEntry {
+    is_dir: bool,
+    /// The content hash, for the small enough files.
+    hash: Option<u64>,
+    /// The file was found larger than the hashed length, it is not read again.
+    too_large: bool,
+}
+
+type Snapshot = HashMap<String, SnapshotEntry>;
+
+/// The 64 bits FNV-1a hash, cheap enough to run on every poll.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+fn flatten(entries: Vec<FileEntry>, snapshot: &mut Snapshot) {
+    for entry in entries {
+        let is_dir = entry.children.is_some();
+        snapshot.insert(
+            entry.path,
+            SnapshotEntry {
+                is_dir,
+                hash: None,
+                too_large: false,
+            },
+        );
+        if let Some(children) = entry.children {
+            flatten(children, snapshot);
+        }
+    }
+}
+
+/// Diff two snapshots into events, sorted by path.
+fn diff(old: &Snapshot, new: &Snapshot) -> Vec<WatchEvent> {
+    let mut removed = old
+        .keys()
+        .filter(|path| !new.contains_key(*path))
+        .collect::<Vec<_>>();
+    let mut created = new
+        .keys()
+        .filter(|path| !old.contains_key(*path))
+        .collect::<Vec<_>>();
+    removed.sort();
+    created.sort();
+    let mut events = Vec::new();
+    let mut renamed = HashSet::new();
+    for from in removed {
+        let entry = old[from];
+        let to = created.iter().copied().find(|to| {
+            let candidate = new[*to];
+            !renamed.contains(*to)
+                && candidate.is_dir == entry.is_dir
+                && (candidate.hash.is_some() && candidate.hash == entry.hash
+                    || file_name(to) == file_name(from))
+        });
+        match to {
+            Some(to) => {
+                renamed.insert(to);
+                events.push(WatchEvent::Renamed {
+                    from: from.clone(),
+                    to: to.clone(),
+                    is_dir: entry.is_dir,
+                });
+            }
+            None => events.push(WatchEvent::Removed {
+                path: from.clone(),
+                is_dir: entry.is_dir,
+            }),
+        }
+    }
+    events.extend(
+        created
+            .into_iter()
+            .filter(|path| !renamed.contains(*path))
+            .map(|path| WatchEvent::Created {
+                path: path.clone(),
+                is_dir: new[path].is_dir,
+            }),
+    );
+    let mut modified = new
+        .iter()
+        .filter(|(path, entry)| {
+            old.get(*path).is_some_and(|previous| {
+                previous.hash.is_some() && entry.hash.is_some() && previous.hash != entry.hash
+            })
+        })
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    modified.sort();
+    events.extend(
+        modified
+            .into_iter()
+            .map(|path| WatchEvent::Modified { path }),
+    );
+    events
+}
+
+/// Hash the small enough files of `snapshot`.
+///
+/// Tauri v1 can't tell the size of a file without reading it, so each file is read once:
+/// the read contents stay in the JavaScript memory until their length is checked,
+/// and the files found too large are skipped by the next snapshots.
+async fn hash_files(
+    snapshot: &mut Snapshot,
+    previous: &Snapshot,
+    max_len: u32,
+    options: FsOptions,
+) {
+    for (path, entry) in snapshot.iter_mut() {
+        let previous = previous.get(path).filter(|previous| !previous.is_dir);
+        if entry.is_dir || previous.is_some_and(|previous| previous.too_large) {
+            entry.too_large = !entry.is_dir;
+            continue;
+        }
+        match super::read_binary_file_raw(path, Some(options.clone())).await {
+            Ok(contents) if contents.length() <= max_len => {
+                entry.hash = Some(fnv1a(&contents.to_vec()));
+            }
+            Ok(_) => entry.too_large = true,
+            // The file may be being written or removed, the next snapshot will tell.
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                log::warn!("Cannot read {path} to hash it: {_err}");
+                entry.hash = previous.and_then(|previous| previous.hash);
+            }
+        }
+    }
+}
+
+/// The absolute path of the watched file, in the same form as the paths listed by [`read_dir`](super::read_dir).
+async fn absolute(path: &str, dir: Option<BaseDirectory>) -> crate::Result<String> {
+    let mut paths = Vec::new();
+    if let Some(dir) = dir {
+        paths.push(dir.resolve().await?);
+    }
+    paths.push(path.to_string());
+    crate::path::resolve(paths).await
+}
+
+async fn take_snapshot(
+    path: String,
+    options: FsDirOptions,
+    max_hashed_len: Option<u32>,
+    previous: Snapshot,
+) -> crate::Result<Snapshot> {
+    let fs_options = FsOptions {
+        dir: options.dir,
+        ..Default::default()
+    };
+    let mut snapshot = Snapshot::new();
+    if !super::exists(&path, Some(fs_options.clone())).await? {
+        return Ok(snapshot);
+    }
+    let dir = options.dir;
+    match super::read_dir(&path, Some(options)).await {
+        Ok(entries) => flatten(entries, &mut snapshot),
+        Err(error) if error.kind() == ErrorKind::NotADirectory => {
+            snapshot.insert(
+                absolute(&path, dir).await?,
+                SnapshotEntry {
+                    is_dir: false,
+                    hash: None,
+                    too_large: false,
+                },
+            );
+        }
+        Err(error) => return Err(error),
+    }
+    if let Some(max_len) = max_hashed_len {
+        hash_files(&mut snapshot, &previous, max_len, fs_options).await;
+    }
+    Ok(snapshot)
+}
+
+/// A [`Stream`] of [`WatchEvent`], created with [`watch`].
+pub struct Watch {
+    path: String,
+    options: FsDirOptions,
+    interval: Duration,
+    max_hashed_len: Option<u32>,
+    /// The baseline, then the last snapshot.
+    snapshot: Option<Snapshot>,
+    /// The first snapshot failed: the next one becomes the baseline, after the interval.
+    retrying_baseline: bool,
+    pending: VecDeque<WatchEvent>,
+    polling: Option<LocalBoxFuture<'static, crate::Result<Snapshot>>>,
+}
+
+impl Watch {
+    /// Also notice content changes, through a hash of the files up to `max_len` bytes long.
+    ///
+    /// Every file is read once, the larger ones are then skipped.
+    /// Tauri v1 has no API for the size or modification time of a file, so the small ones are read
+    /// and hashed again at every poll, even when they didn't change: keep `max_len` small on large trees.
+    /// A file that can't be read keeps its previous hash until the next poll.
+    pub fn hash_small_files(mut self, max_len: u32) -> Self {
+        self.max_hashed_len = Some(max_len);
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn poll_snapshot(&self) -> LocalBoxFuture<'static, crate::Result<Snapshot>> {
+        let previous = match (&self.snapshot, self.max_hashed_len) {
+            (Some(snapshot), Some(_)) => snapshot.clone(),
+            _ => Snapshot::new(),
+        };
+        let snapshot = take_snapshot(
+            self.path.clone(),
+            self.options.clone(),
+            self.max_hashed_len,
+            previous,
+        );
+        if self.snapshot.is_none() && !self.retrying_baseline {
+            return snapshot.boxed_local();
+        }
+        let interval = self.interval;
+        async move {
+            sleep(interval).await;
+            snapshot.await
+        }
+        .boxed_local()
+    }
+}
+
+impl Stream for Watch {
+    type Item = crate::Result<WatchEvent>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            let mut polling = match self.polling.take() {
+                Some(polling) => polling,
+                None => self.poll_snapshot(),
+            };
+            match polling.poll_unpin(cx) {
+                Poll::Ready(Ok(snapshot)) => {
+                    if let Some(previous) = &self.snapshot {
+                        let events = diff(previous, &snapshot);
+                        self.pending.extend(events);
+                    }
+                    self.snapshot = Some(snapshot);
+                }
+                Poll::Ready(Err(error)) => {
+                    // No events are diffed against a missing baseline, but the retry waits for the interval.
+                    if self.snapshot.is_none() {
+                        self.retrying_baseline = true;
+                    }
+                    return Poll::Ready(Some(Err(error)));
+                }
+                Poll::Pending => {
+                    self.polling = Some(polling);
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+/// Watch `path` by polling it every `interval`.
+///
+/// The first snapshot is taken right away and only the changes after it are reported.
+/// A missing `path` is watched too: its creation shows up as [`WatchEvent::Created`] events.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use futures::StreamExt;
+/// use tauri_global_sys::fs::{watch::watch, BaseDirectory, FsDirOptions};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let options = FsDirOptions {
+///     dir: Some(BaseDirectory::Download),
+///     recursive: Some(false),
+/// };
+/// let mut downloads = watch("", options, Duration::from_secs(2)).hash_small_files(64 * 1024);
+/// while let Some(event) = downloads.next().await {
+///     println!("{:?}", event?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn watch(path: &str, options: FsDirOptions, interval: Duration) -> Watch {
+    Watch {
+        path: path.to_string(),
+        options,
+        interval,
+        max_hashed_len: None,
+        snapshot: None,
+        retrying_baseline: false,
+        pending: VecDeque::new(),
+        polling: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, fnv1a, Snapshot, SnapshotEntry, WatchEvent};
+
+    fn snapshot(entries: &[(&str, bool, Option<u64>)]) -> Snapshot {
+        entries
+            .iter()
+            .map(|(path, is_dir, hash)| {
+                (
+                    path.to_string(),
+                    SnapshotEntry {
+                        is_dir: *is_dir,
+                        hash: *hash,
+                        too_large: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn created_and_removed() {
+        let old = snapshot(&[("/d/a.txt", false, None), ("/d/sub", true, None)]);
+        let new = snapshot(&[("/d/b.txt", false, None), ("/d/sub", true, None)]);
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                WatchEvent::Removed {
+                    path: "/d/a.txt".into(),
+                    is_dir: false
+                },
+                WatchEvent::Created {
+                    path: "/d/b.txt".into(),
+                    is_dir: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed() {
+        let old = snapshot(&[("/d/a.txt", false, Some(1)), ("/d/c.txt", false, None)]);
+        let new = snapshot(&[("/d/b.txt", false, Some(1)), ("/d/sub/c.txt", false, None)]);
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                WatchEvent::Renamed {
+                    from: "/d/a.txt".into(),
+                    to: "/d/b.txt".into(),
+                    is_dir: false
+                },
+                WatchEvent::Renamed {
+                    from: "/d/c.txt".into(),
+                    to: "/d/sub/c.txt".into(),
+                    is_dir: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn modified() {
+        let old = snapshot(&[("/d/a.txt", false, Some(1)), ("/d/large", false, None)]);
+        let new = snapshot(&[("/d/a.txt", false, Some(2)), ("/d/large", false, None)]);
+        assert_eq!(
+            diff(&old, &new),
+            vec![WatchEvent::Modified {
+                path: "/d/a.txt".into()
+            }]
+        );
+        assert!(diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn hash() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(fnv1a(b"ab"), fnv1a(b"ba"));
+    }
+}
@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, future::Future};
 
+use bytes::Bytes;
 use classes::{RawBody, RawClient, RawResponse};
 use js_sys::{JsString, Map as JsMap, Object, Uint8Array};
 use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::spawn_local;
 
 use super::{HttpVerb, ResponseType};
@@ -101,7 +102,7 @@ pub struct FilePart<T> {
     pub mime: Option<String>,
 }
 
-impl FilePart<Vec<u8>> {
+impl<T: AsRef<[u8]>> FilePart<T> {
     pub fn into_js_value(&self) -> JsValue {
         let map = JsMap::new();
         map.set(
@@ -115,14 +116,14 @@ impl FilePart<Vec<u8>> {
         map.set(
             &JsString::from("mime"),
             &self
-                .file_name
+                .mime
                 .as_ref()
                 .map(JsValue::from)
                 .unwrap_or(JsValue::undefined()),
         );
         let file = match &self.file {
             FilePartFile::Path(s) => JsValue::from(s),
-            FilePartFile::File(file) => u8_slice_to_uint_array(file).into(),
+            FilePartFile::File(file) => Uint8Array::from(file.as_ref()).into(),
         };
         map.set(&JsString::from("file"), &file);
         Object::from_entries(&map).unwrap().into()
@@ -134,15 +135,15 @@ impl FilePart<Vec<u8>> {
 #[serde(untagged)]
 pub enum Part {
     String(String),
-    Bytes(Vec<u8>),
-    File(FilePart<Vec<u8>>),
+    Bytes(Bytes),
+    File(FilePart<Bytes>),
 }
 
 impl Part {
     pub fn into_js_value(&self) -> JsValue {
         match self {
             Part::String(s) => JsString::from(s.as_str()).into(),
-            Part::Bytes(items) => u8_slice_to_uint_array(items).into(),
+            Part::Bytes(items) => Uint8Array::from(items.as_ref()).into(),
             Part::File(file_part) => file_part.into_js_value(),
         }
     }
@@ -156,22 +157,33 @@ impl From<String> for Part {
 
 impl From<Vec<u8>> for Part {
     fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value.into())
+    }
+}
+
+impl From<Bytes> for Part {
+    fn from(value: Bytes) -> Self {
         Self::Bytes(value)
     }
 }
 
-impl From<FilePart<Vec<u8>>> for Part {
-    fn from(value: FilePart<Vec<u8>>) -> Self {
+impl From<FilePart<Bytes>> for Part {
+    fn from(value: FilePart<Bytes>) -> Self {
         Self::File(value)
     }
 }
 
-fn u8_slice_to_uint_array(bytes: &[u8]) -> Uint8Array {
-    let array = Uint8Array::new_with_length(bytes.len() as u32);
-    for (idx, byte) in bytes.iter().enumerate() {
-        array.set_index(idx as u32, *byte);
+impl From<FilePart<Vec<u8>>> for Part {
+    fn from(value: FilePart<Vec<u8>>) -> Self {
+        Self::File(FilePart {
+            file: match value.file {
+                FilePartFile::Path(path) => FilePartFile::Path(path),
+                FilePartFile::File(file) => FilePartFile::File(file.into()),
+            },
+            file_name: value.file_name,
+            mime: value.mime,
+        })
     }
-    array
 }
 
 fn body_form_to_object(map: &HashMap<String, Part>) -> Object {
@@ -185,7 +197,7 @@ fn body_form_to_object(map: &HashMap<String, Part>) -> Object {
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum Body {
-    Bytes(Vec<u8>),
+    Bytes(Bytes),
     Form(HashMap<String, Part>),
     Text(String),
 }
@@ -199,13 +211,25 @@ impl Default for Body {
 impl Body {
     pub fn into_raw(&self) -> RawBody {
         match self {
-            Body::Bytes(items) => RawBody::bytes(u8_slice_to_uint_array(items).into()),
+            Body::Bytes(items) => RawBody::bytes(Uint8Array::from(items.as_ref()).into()),
             Body::Form(hash_map) => RawBody::form(body_form_to_object(hash_map).into()),
             Body::Text(text) => RawBody::text(text),
         }
     }
 }
 
+impl From<Vec<u8>> for Body {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value.into())
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(value: Bytes) -> Self {
+        Self::Bytes(value)
+    }
+}
+
 impl From<Body> for RawBody {
     fn from(value: Body) -> Self {
         value.into_raw()
@@ -293,6 +317,23 @@ impl RawClientWrapper {
             .as_ref()
             .ok_or(crate::Error::RawClientWrapperInnerEmpty)
     }
+    /// Run a `Client` method through the [`session`](crate::session), like [`raw_fetch`].
+    async fn session_call<Fut>(
+        method: &str,
+        args: &[&JsValue],
+        call: Fut,
+    ) -> crate::Result<RawResponse>
+    where
+        Fut: Future<Output = Result<RawResponse, JsValue>>,
+    {
+        let function = format!("Client.{method}");
+        let res = crate::session::module_call("http", &function, args, async {
+            call.await.map(JsValue::from)
+        })
+        .await?;
+        // A replayed response is a plain object with the same fields.
+        Ok(res.unchecked_into())
+    }
     /// Perform a `DELETE` request with the actual client.
     pub async fn raw_delete(
         &self,
@@ -300,9 +341,11 @@ impl RawClientWrapper {
         option: RequestOptions,
     ) -> crate::Result<RawResponse> {
         let inner = self.inner()?;
-        Ok(inner
-            .delete(url, serde_wasm_bindgen::to_value(&option)?)
-            .await?)
+        let options = serde_wasm_bindgen::to_value(&option)?;
+        Self::session_call("delete", &[&url.into(), &options], async {
+            inner.delete(url, options.clone()).await
+        })
+        .await
     }
     /// Perform a `DELETE` request
     /// but unlike [`raw_delete`](Self::raw_delete), this allows you to deserialize the response data.
@@ -318,9 +361,11 @@ impl RawClientWrapper {
     /// Perform a `GET` request with the actual client.
     pub async fn raw_get(&self, url: &str, option: RequestOptions) -> crate::Result<RawResponse> {
         let inner = self.inner()?;
-        Ok(inner
-            .get(url, serde_wasm_bindgen::to_value(&option)?)
-            .await?)
+        let options = serde_wasm_bindgen::to_value(&option)?;
+        Self::session_call("get", &[&url.into(), &options], async {
+            inner.get(url, options.clone()).await
+        })
+        .await
     }
     /// Perform a `GET` request
     /// but unlike [`raw_get`](Self::raw_get), this allows you to deserialize the response data.
@@ -336,9 +381,11 @@ impl RawClientWrapper {
     /// Perform a `PATCH` request with the actual client.
     pub async fn raw_patch(&self, url: &str, option: RequestOptions) -> crate::Result<RawResponse> {
         let inner = self.inner()?;
-        Ok(inner
-            .patch(url, serde_wasm_bindgen::to_value(&option)?)
-            .await?)
+        let options = serde_wasm_bindgen::to_value(&option)?;
+        Self::session_call("patch", &[&url.into(), &options], async {
+            inner.patch(url, options.clone()).await
+        })
+        .await
     }
     /// Perform a `PATCH` request
     /// but unlike [`raw_patch`](Self::raw_patch), this allows you to deserialize the response data.
@@ -362,9 +409,12 @@ impl RawClientWrapper {
         B: Into<RawBody>,
     {
         let inner = self.inner()?;
-        Ok(inner
-            .post(url, body.into(), serde_wasm_bindgen::to_value(&option)?)
-            .await?)
+        let body: RawBody = body.into();
+        let options = serde_wasm_bindgen::to_value(&option)?;
+        Self::session_call("post", &[&url.into(), &body, &options], async {
+            inner.post(url, body.clone(), options.clone()).await
+        })
+        .await
     }
     /// Perform a `POST` request
     /// but unlike [`raw_post`](Self::raw_post), this allows you to deserialize the response data.
@@ -393,9 +443,12 @@ impl RawClientWrapper {
         B: Into<RawBody>,
     {
         let inner = self.inner()?;
-        Ok(inner
-            .put(url, body.into(), serde_wasm_bindgen::to_value(&option)?)
-            .await?)
+        let body: RawBody = body.into();
+        let options = serde_wasm_bindgen::to_value(&option)?;
+        Self::session_call("put", &[&url.into(), &body, &options], async {
+            inner.put(url, body.clone(), options.clone()).await
+        })
+        .await
     }
     /// Perform a `PUT` request
     /// but unlike [`raw_put`](Self::raw_put), this allows you to deserialize the response data.
@@ -421,9 +474,11 @@ impl RawClientWrapper {
             ));
         }
         let inner = self.inner()?;
-        Ok(inner
-            .request(serde_wasm_bindgen::to_value(&option)?)
-            .await?)
+        let options = serde_wasm_bindgen::to_value(&option)?;
+        Self::session_call("request", &[&options], async {
+            inner.request(options.clone()).await
+        })
+        .await
     }
 
     /// Perform an HTTP request
@@ -462,7 +517,15 @@ impl Drop for RawClientWrapper {
 
 /// Perform an HTTP request using the default client.
 pub async fn raw_fetch(url: &str, options: FetchOptions) -> crate::Result<RawResponse> {
-    Ok(functions::rawFetch(url, serde_wasm_bindgen::to_value(&options)?).await?)
+    let options = serde_wasm_bindgen::to_value(&options)?;
+    let res = crate::session::module_call("http", "fetch", &[&url.into(), &options], async {
+        functions::rawFetch(url, options.clone())
+            .await
+            .map(JsValue::from)
+    })
+    .await?;
+    // A replayed response is a plain object with the same fields.
+    Ok(res.unchecked_into())
 }
 
 /// Same as [`raw_fetch`] but this one allows you to deserialize the response data.
@@ -35,10 +35,14 @@ pub mod path;
 /// The equivalent of the [`window.__TAURI__.process`](https://v1.tauri.app/v1/api/js/process) module.
 ///
 pub mod process;
+/// Record and replay the IPC calls as JSON fixtures.
+///
+pub mod session;
 /// The equivalent of the [`window.__TAURI__.tauri`](https://v1.tauri.app/v1/api/js/tauri) module.
 ///
 pub mod tauri;
+mod timer;
 
-pub use error::Error;
+pub use error::{Error, ErrorKind};
 
 pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
@@ -16,8 +16,15 @@
 //! ```
 //!
 //! It is recommended to allowlist only the APIs you use for optimal bundle size and security.
+pub mod file_name;
+pub mod pure;
 pub mod raw;
 
+pub use file_name::{sanitize_file_name, unique_name};
+pub use pure::{Component, PathStyle, TauriPath, TauriPathBuf};
+
+use crate::fs::BaseDirectory;
+
 /// Provides the platform-specific path segment delimiter:
 /// - `;` on Windows
 /// - `:` on POSIX
@@ -36,6 +43,17 @@ pub fn sep() -> String {
     raw::SEP.with(Clone::clone)
 }
 
+/// Returns the last portion of a `path`, without `ext` if it ends with it.
+/// Trailing directory separators are ignored.
+///
+/// Ref: <http://v1.tauri.app/v1/api/js/path#basename>
+pub async fn basename(path: &str, ext: Option<&str>) -> crate::Result<String> {
+    raw::basename(path, ext.map(Into::into))
+        .await?
+        .as_string()
+        .ok_or(crate::Error::JsStringToString)
+}
+
 /// Returns the directory name of a path.
 /// Trailing directory separators are ignored.
 ///
@@ -98,6 +116,39 @@ pub async fn resolve(paths: Vec<String>) -> crate::Result<String> {
         .into())
 }
 
+/// Split a leading scope variable like `$APPDATA` from the rest of `path`.
+fn split_variable(path: &str) -> crate::Result<Option<(BaseDirectory, &str)>> {
+    if !path.starts_with('$') {
+        return Ok(None);
+    }
+    let end = path.find(['/', '\\']).unwrap_or(path.len());
+    let base_dir =
+        BaseDirectory::from_variable(&path[..end]).ok_or_else(|| crate::Error::InvalidPath {
+            path: path.into(),
+            reason: "unknown scope variable",
+        })?;
+    Ok(Some((base_dir, &path[end..])))
+}
+
+/// Expand the leading scope variable of `path`, like `$APPDATA/db/main.sqlite`,
+/// with [`BaseDirectory::resolve`]. The paths without variable are returned as is.
+///
+/// See [`BaseDirectory::variable`] for the variables.
+///
+/// ```rust,no_run
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let db = tauri_global_sys::path::expand("$APPDATA/db/main.sqlite").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn expand(path: &str) -> crate::Result<String> {
+    let Some((base_dir, rest)) = split_variable(path)? else {
+        return Ok(path.to_string());
+    };
+    let resolved = base_dir.resolve().await?;
+    Ok(TauriPath::new(&resolved).join(rest).into_string())
+}
+
 macro_rules! dirs {
     ($($name:ident => $raw:ident,)*) => {
         $(
@@ -134,3 +185,30 @@ dirs! {
     template_dir => templateDir,
     video_dir => videoDir,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::split_variable;
+    use crate::fs::BaseDirectory;
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            split_variable("$APPDATA/db/main.sqlite").unwrap(),
+            Some((BaseDirectory::AppData, "/db/main.sqlite"))
+        );
+        assert_eq!(
+            split_variable("$APP\\a").unwrap(),
+            Some((BaseDirectory::App, "\\a"))
+        );
+        assert_eq!(
+            split_variable("$EXE").unwrap(),
+            Some((BaseDirectory::Executable, ""))
+        );
+        assert_eq!(split_variable("/a/$APPDATA").unwrap(), None);
+        assert!(matches!(
+            split_variable("$APPDATAX/a"),
+            Err(crate::Error::InvalidPath { .. })
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+//! File names built from user input, valid on every OS.
+use crate::os::OsType;
+
+use super::{PathStyle, TauriPath};
+
+/// The characters Windows refuses in a file name, besides the control characters.
+const WINDOWS_FORBIDDEN: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// The device names Windows reserves, even with an extension.
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$", "COM0", "COM1", "COM2", "COM3", "COM4",
+    "COM5", "COM6", "COM7", "COM8", "COM9", "LPT0", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9",
+];
+
+/// Remove the trailing `.` and spaces, which Windows drops from file names.
+fn trim_windows_end(name: &mut String) {
+    name.truncate(name.trim_end_matches(['.', ' ']).len());
+}
+
+/// The maximum length of a file name: 255 UTF-16 units on Windows and 255 bytes elsewhere.
+fn fits(name: &str, os: OsType) -> bool {
+    match os {
+        OsType::WindowsNt => name.encode_utf16().count() <= 255,
+        OsType::Linux | OsType::Darwin => name.len() <= 255,
+    }
+}
+
+/// The name split before the extension, a leading `.` not starting one.
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(index) if index > 0 => name.split_at(index),
+        _ => (name, ""),
+    }
+}
+
+/// Make `name` a valid file name on `os`.
+///
+/// The forbidden characters are replaced with `_`, the Windows reserved names are prefixed with `_`,
+/// and the too long names are shortened, keeping their extension.
+///
+/// ```rust
+/// use tauri_global_sys::{os::OsType, path::sanitize_file_name};
+///
+/// assert_eq!(sanitize_file_name("Q3: sales?.csv", OsType::WindowsNt), "Q3_ sales_.csv");
+/// assert_eq!(sanitize_file_name("con.txt", OsType::WindowsNt), "_con.txt");
+/// assert_eq!(sanitize_file_name("Q3: sales?.csv", OsType::Linux), "Q3: sales?.csv");
+/// ```
+pub fn sanitize_file_name(name: &str, os: OsType) -> String {
+    let forbidden = |c: char| {
+        c.is_control()
+            || c == '/'
+            || match os {
+                OsType::WindowsNt => WINDOWS_FORBIDDEN.contains(&c),
+                OsType::Darwin => c == ':',
+                OsType::Linux => false,
+            }
+    };
+    let mut sanitized: String = name
+        .trim()
+        .chars()
+        .map(|c| if forbidden(c) { '_' } else { c })
+        .collect();
+    if os == OsType::WindowsNt {
+        trim_windows_end(&mut sanitized);
+    }
+    if !fits(&sanitized, os) {
+        let (stem, extension) = split_extension(&sanitized);
+        let (mut stem, extension) = if fits(extension, os) && extension.len() < 32 {
+            (stem.to_string(), extension)
+        } else {
+            (sanitized.clone(), "")
+        };
+        while !fits(&format!("{stem}{extension}"), os) {
+            stem.pop();
+        }
+        sanitized = format!("{stem}{extension}");
+    }
+    if os == OsType::WindowsNt {
+        // The shortened name may end with a `.` or a space again.
+        trim_windows_end(&mut sanitized);
+        let device = sanitized.split('.').next().unwrap_or_default().trim_end();
+        if WINDOWS_RESERVED
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(device))
+        {
+            sanitized.insert(0, '_');
+        }
+    }
+    if sanitized.is_empty() || sanitized == "." || sanitized == ".." {
+        return "_".into();
+    }
+    sanitized
+}
+
+/// `name` with ` (n)` inserted before its extension.
+fn numbered(name: &str, n: u32) -> String {
+    let (stem, extension) = split_extension(name);
+    format!("{stem} ({n}){extension}")
+}
+
+/// The path of `name` in `dir`, with ` (1)`, ` (2)`… appended if it already exists.
+///
+/// `dir` is absolute or starts with a scope variable, see [`expand`](super::expand).
+///
+/// ```rust,no_run
+/// use tauri_global_sys::{os, path};
+///
+/// # async fn run() -> Result<(), tauri_global_sys::Error> {
+/// let name = path::sanitize_file_name("Q3: sales?.csv", os::type_().await?);
+/// let path = path::unique_name("$DOWNLOAD", &name).await?;
+/// tauri_global_sys::fs::write_text_file(&path, "id,amount\n", None).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn unique_name(dir: &str, name: &str) -> crate::Result<String> {
+    let dir = super::expand(dir).await?;
+    let dir = TauriPath::with_style(&dir, PathStyle::host());
+    let mut path = dir.join(name).into_string();
+    let mut n = 1;
+    while crate::fs::exists(&path, None).await? {
+        path = dir.join(&numbered(name, n)).into_string();
+        n += 1;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{numbered, sanitize_file_name};
+    use crate::os::OsType;
+
+    #[test]
+    fn sanitize() {
+        for (name, windows, unix) in [
+            ("a/b", "a_b", "a_b"),
+            ("a:b?*|\"<>\\", "a_b_______", "a:b?*|\"<>\\"),
+            ("  report. . ", "report", "report. ."),
+            ("tab\there", "tab_here", "tab_here"),
+            ("CON", "_CON", "CON"),
+            ("lpt1.tar.gz", "_lpt1.tar.gz", "lpt1.tar.gz"),
+            ("console.txt", "console.txt", "console.txt"),
+            ("com0", "_com0", "com0"),
+            ("CONOUT$.log", "_CONOUT$.log", "CONOUT$.log"),
+            ("..", "_", "_"),
+            ("", "_", "_"),
+        ] {
+            assert_eq!(
+                sanitize_file_name(name, OsType::WindowsNt),
+                windows,
+                "{name}"
+            );
+            assert_eq!(sanitize_file_name(name, OsType::Linux), unix, "{name}");
+        }
+        assert_eq!(sanitize_file_name("a:b", OsType::Darwin), "a_b");
+    }
+
+    #[test]
+    fn shorten() {
+        let long = format!("{}.csv", "é".repeat(200));
+        let windows = sanitize_file_name(&long, OsType::WindowsNt);
+        assert_eq!(windows, long);
+        let linux = sanitize_file_name(&long, OsType::Linux);
+        assert_eq!(linux, format!("{}.csv", "é".repeat(125)));
+        assert_eq!(
+            sanitize_file_name(&"a".repeat(300), OsType::Linux),
+            "a".repeat(255)
+        );
+        // The overlong extension is cut, leaving a trailing space Windows would reject.
+        let cut = format!("{} .{}", "a".repeat(254), "b".repeat(40));
+        assert_eq!(sanitize_file_name(&cut, OsType::WindowsNt), "a".repeat(254));
+    }
+
+    #[test]
+    fn numbering() {
+        assert_eq!(numbered("report.csv", 1), "report (1).csv");
+        assert_eq!(numbered("notes", 2), "notes (2)");
+        assert_eq!(numbered(".env", 3), ".env (3)");
+    }
+}
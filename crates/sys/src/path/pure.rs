@@ -0,0 +1,547 @@
+//! Synchronous path manipulation, following the semantics of Node's `path.posix` and `path.win32`
+//! used by the async `path` functions, without the IPC round trip.
+use std::fmt;
+
+/// The separators of a [`TauriPath`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathStyle {
+    /// `/` separated, `:` delimited.
+    Posix,
+    /// `\` or `/` separated, `;` delimited, with drive letters and UNC prefixes.
+    Windows,
+}
+
+impl PathStyle {
+    /// The style of the host OS, given by [`sep`](super::sep).
+    pub fn host() -> Self {
+        if super::sep() == "\\" {
+            Self::Windows
+        } else {
+            Self::Posix
+        }
+    }
+
+    pub fn separator(self) -> char {
+        match self {
+            Self::Posix => '/',
+            Self::Windows => '\\',
+        }
+    }
+
+    /// The delimiter of the path lists, as given by [`delimiter`](super::delimiter) for the host.
+    pub fn delimiter(self) -> char {
+        match self {
+            Self::Posix => ':',
+            Self::Windows => ';',
+        }
+    }
+
+    pub fn is_separator(self, c: char) -> bool {
+        match self {
+            Self::Posix => c == '/',
+            Self::Windows => c == '/' || c == '\\',
+        }
+    }
+
+    /// Split a list of paths, like the `PATH` environment variable, skipping the empty ones.
+    pub fn split_list(self, list: &str) -> impl Iterator<Item = &str> {
+        list.split(self.delimiter()).filter(|path| !path.is_empty())
+    }
+}
+
+/// A component of a [`TauriPath`], see [`TauriPath::components`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component<'a> {
+    /// A Windows drive (`C:`) or UNC share (`\\server\share`).
+    Prefix(&'a str),
+    RootDir,
+    CurDir,
+    ParentDir,
+    Normal(&'a str),
+}
+
+/// The byte offsets of the prefix and the root of a path.
+struct Root {
+    prefix_end: usize,
+    root_end: usize,
+}
+
+impl Root {
+    fn has_root(&self) -> bool {
+        self.root_end > self.prefix_end
+    }
+}
+
+fn leading_separators(path: &str, style: PathStyle) -> usize {
+    path.len() - path.trim_start_matches(|c| style.is_separator(c)).len()
+}
+
+fn parse_root(path: &str, style: PathStyle) -> Root {
+    let prefix_end = match style {
+        PathStyle::Posix => 0,
+        PathStyle::Windows => {
+            let bytes = path.as_bytes();
+            let is_separator = |index: usize| {
+                bytes
+                    .get(index)
+                    .is_some_and(|byte| style.is_separator(*byte as char))
+            };
+            if is_separator(0) && is_separator(1) {
+                // `\\server\share`, the server and the share being both required.
+                let rest = &path[2..];
+                let server = rest.find(|c| style.is_separator(c));
+                let share = server.and_then(|server| {
+                    let share = &rest[server + 1..];
+                    let len = share.find(|c| style.is_separator(c)).unwrap_or(share.len());
+                    (server > 0 && len > 0).then_some(2 + server + 1 + len)
+                });
+                share.unwrap_or(0)
+            } else if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+                2
+            } else {
+                0
+            }
+        }
+    };
+    Root {
+        prefix_end,
+        root_end: prefix_end + leading_separators(&path[prefix_end..], style),
+    }
+}
+
+/// A borrowed path, see [`TauriPathBuf`] for the owned one.
+///
+/// ```rust
+/// use tauri_global_sys::path::{PathStyle, TauriPath};
+///
+/// let path = TauriPath::with_style("/data/exports/report.csv", PathStyle::Posix);
+/// assert_eq!(path.file_name(), Some("report.csv"));
+/// assert_eq!(path.extension(), Some("csv"));
+/// assert_eq!(path.parent().unwrap().as_str(), "/data/exports");
+/// assert_eq!(path.join("../../archive").as_str(), "/data/archive");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TauriPath<'a> {
+    path: &'a str,
+    style: PathStyle,
+}
+
+impl fmt::Debug for TauriPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.path, f)
+    }
+}
+
+impl fmt::Display for TauriPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.path)
+    }
+}
+
+impl<'a> TauriPath<'a> {
+    /// A path in the [host style](PathStyle::host).
+    pub fn new(path: &'a str) -> Self {
+        Self::with_style(path, PathStyle::host())
+    }
+
+    pub fn with_style(path: &'a str, style: PathStyle) -> Self {
+        Self { path, style }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.path
+    }
+
+    pub fn style(&self) -> PathStyle {
+        self.style
+    }
+
+    pub fn to_path_buf(&self) -> TauriPathBuf {
+        TauriPathBuf::with_style(self.path, self.style)
+    }
+
+    /// Whether the path has a root, like Node's `isAbsolute`: `C:` alone is relative on Windows.
+    pub fn is_absolute(&self) -> bool {
+        parse_root(self.path, self.style).has_root()
+    }
+
+    /// The path after the root, without its trailing separators.
+    fn tail(&self) -> (Root, &'a str) {
+        let root = parse_root(self.path, self.style);
+        let tail = self.path[root.root_end..].trim_end_matches(|c| self.style.is_separator(c));
+        (root, tail)
+    }
+
+    /// The components, the `.` being skipped unless they start a relative path.
+    pub fn components(&self) -> Vec<Component<'a>> {
+        let root = parse_root(self.path, self.style);
+        let mut components = Vec::new();
+        if root.prefix_end > 0 {
+            components.push(Component::Prefix(&self.path[..root.prefix_end]));
+        }
+        if root.has_root() {
+            components.push(Component::RootDir);
+        }
+        let parts = self.path[root.root_end..]
+            .split(|c| self.style.is_separator(c))
+            .filter(|part| !part.is_empty());
+        for (index, part) in parts.enumerate() {
+            match part {
+                "." if index == 0 && root.root_end == 0 => components.push(Component::CurDir),
+                "." => {}
+                ".." => components.push(Component::ParentDir),
+                part => components.push(Component::Normal(part)),
+            }
+        }
+        components
+    }
+
+    /// The last component, like Node's `basename`, [`None`] if there is only a root.
+    pub fn file_name(&self) -> Option<&'a str> {
+        let (_, tail) = self.tail();
+        let name = match tail.rfind(|c| self.style.is_separator(c)) {
+            Some(index) => &tail[index + 1..],
+            None => tail,
+        };
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// The [`file_name`](Self::file_name) and its extension, split on the last `.` not starting the name.
+    fn split_extension(&self) -> Option<(&'a str, Option<&'a str>)> {
+        let name = self.file_name()?;
+        if name == ".." {
+            return Some((name, None));
+        }
+        Some(match name.rfind('.') {
+            Some(index) if index > 0 => (&name[..index], Some(&name[index + 1..])),
+            _ => (name, None),
+        })
+    }
+
+    /// The file name without its extension.
+    pub fn file_stem(&self) -> Option<&'a str> {
+        self.split_extension().map(|(stem, _)| stem)
+    }
+
+    /// The extension without the `.`, Node's `extname` being `"." + extension`.
+    pub fn extension(&self) -> Option<&'a str> {
+        self.split_extension().and_then(|(_, extension)| extension)
+    }
+
+    /// The path without its last component, like Node's `dirname`,
+    /// [`None`] for a root or an empty path.
+    pub fn parent(&self) -> Option<TauriPath<'a>> {
+        let (root, tail) = self.tail();
+        if tail.is_empty() {
+            return None;
+        }
+        let parent = match tail.rfind(|c| self.style.is_separator(c)) {
+            Some(index) => {
+                let parent = tail[..index].trim_end_matches(|c| self.style.is_separator(c));
+                &self.path[..root.root_end + parent.len()]
+            }
+            None if root.root_end > 0 => &self.path[..root.root_end],
+            None => ".",
+        };
+        Some(Self::with_style(parent, self.style))
+    }
+
+    /// Append `segment` and normalize, like Node's `join`: an absolute `segment` doesn't replace the path.
+    pub fn join(&self, segment: &str) -> TauriPathBuf {
+        let joined = match (self.path.is_empty(), segment.is_empty()) {
+            (_, true) => self.path.to_string(),
+            (true, false) => segment.to_string(),
+            (false, false) => format!("{}{}{segment}", self.path, self.style.separator()),
+        };
+        TauriPath::with_style(&joined, self.style).normalize()
+    }
+
+    /// The path with the extension replaced by `extension`, or removed if it is empty.
+    pub fn with_extension(&self, extension: &str) -> TauriPathBuf {
+        let (root, tail) = self.tail();
+        let mut path = self.path[..root.root_end + tail.len()].to_string();
+        let Some((_, current)) = self.split_extension() else {
+            return TauriPathBuf::with_style(path, self.style);
+        };
+        if let Some(current) = current {
+            path.truncate(path.len() - current.len() - 1);
+        }
+        if !extension.is_empty() {
+            path.push('.');
+            path.push_str(extension);
+        }
+        TauriPathBuf::with_style(path, self.style)
+    }
+
+    /// Resolve the `.` and `..` segments and collapse the separators, like Node's `normalize`.
+    ///
+    /// Unlike the async [`normalize`](super::normalize), the symbolic links are not resolved.
+    pub fn normalize(&self) -> TauriPathBuf {
+        let separator = self.style.separator();
+        let root = parse_root(self.path, self.style);
+        let mut parts: Vec<&str> = Vec::new();
+        for part in self.path[root.root_end..].split(|c| self.style.is_separator(c)) {
+            match part {
+                "" | "." => {}
+                ".." => match parts.last() {
+                    Some(&last) if last != ".." => {
+                        parts.pop();
+                    }
+                    _ if root.has_root() => {}
+                    _ => parts.push(".."),
+                },
+                part => parts.push(part),
+            }
+        }
+        let mut normalized = self.path[..root.prefix_end].replace('/', &separator.to_string());
+        let is_unc = root.prefix_end > 2;
+        if root.has_root() || is_unc {
+            normalized.push(separator);
+        }
+        normalized.push_str(&parts.join(&separator.to_string()));
+        if parts.is_empty() {
+            if !root.has_root() && !is_unc {
+                normalized.push('.');
+            } else {
+                return TauriPathBuf::with_style(normalized, self.style);
+            }
+        }
+        let trailing = self.path[root.root_end..]
+            .chars()
+            .last()
+            .is_some_and(|c| self.style.is_separator(c));
+        if trailing {
+            normalized.push(separator);
+        }
+        TauriPathBuf::with_style(normalized, self.style)
+    }
+}
+
+/// An owned path, see [`TauriPath`].
+///
+/// ```rust
+/// use tauri_global_sys::path::{PathStyle, TauriPathBuf};
+///
+/// let mut path = TauriPathBuf::with_style("C:\\Users\\me", PathStyle::Windows);
+/// path.push("exports/report.txt");
+/// path.set_extension("csv");
+/// assert_eq!(path.as_str(), "C:\\Users\\me\\exports\\report.csv");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct TauriPathBuf {
+    path: String,
+    style: PathStyle,
+}
+
+impl fmt::Debug for TauriPathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.path, f)
+    }
+}
+
+impl fmt::Display for TauriPathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl AsRef<str> for TauriPathBuf {
+    fn as_ref(&self) -> &str {
+        &self.path
+    }
+}
+
+impl From<TauriPathBuf> for String {
+    fn from(path: TauriPathBuf) -> Self {
+        path.path
+    }
+}
+
+impl TauriPathBuf {
+    /// A path in the [host style](PathStyle::host).
+    pub fn new(path: impl Into<String>) -> Self {
+        Self::with_style(path, PathStyle::host())
+    }
+
+    pub fn with_style(path: impl Into<String>, style: PathStyle) -> Self {
+        Self {
+            path: path.into(),
+            style,
+        }
+    }
+
+    pub fn as_path(&self) -> TauriPath<'_> {
+        TauriPath::with_style(&self.path, self.style)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    pub fn into_string(self) -> String {
+        self.path
+    }
+
+    /// See [`TauriPath::join`].
+    pub fn push(&mut self, segment: &str) {
+        *self = self.as_path().join(segment);
+    }
+
+    /// Truncate to the [`parent`](TauriPath::parent), returning `false` if there is none.
+    pub fn pop(&mut self) -> bool {
+        match self.as_path().parent() {
+            Some(parent) => {
+                self.path = parent.as_str().to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// See [`TauriPath::with_extension`].
+    pub fn set_extension(&mut self, extension: &str) {
+        *self = self.as_path().with_extension(extension);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Component, PathStyle, TauriPath};
+
+    fn posix(path: &str) -> TauriPath<'_> {
+        TauriPath::with_style(path, PathStyle::Posix)
+    }
+
+    fn win32(path: &str) -> TauriPath<'_> {
+        TauriPath::with_style(path, PathStyle::Windows)
+    }
+
+    // The expected values are the outputs of Node's `path.posix` and `path.win32`.
+
+    #[test]
+    fn normalize() {
+        for (path, normalized) in [
+            ("", "."),
+            ("./", "./"),
+            ("/a/b/../c/./d", "/a/c/d"),
+            ("/a/b/", "/a/b/"),
+            ("//a//b", "/a/b"),
+            ("/../..", "/"),
+            ("a/../..", ".."),
+            ("a/../", "./"),
+            ("../a/../../b", "../../b"),
+        ] {
+            assert_eq!(posix(path).normalize().as_str(), normalized, "{path}");
+        }
+        for (path, normalized) in [
+            ("C:/a/../b", "C:\\b"),
+            ("c:\\a\\.\\b\\", "c:\\a\\b\\"),
+            ("C:", "C:."),
+            ("C:\\..", "C:\\"),
+            ("C:..\\a", "C:..\\a"),
+            ("//server/share/a/../b", "\\\\server\\share\\b"),
+            ("//server/share", "\\\\server\\share\\"),
+            ("/a/b", "\\a\\b"),
+        ] {
+            assert_eq!(win32(path).normalize().as_str(), normalized, "{path}");
+        }
+    }
+
+    #[test]
+    fn join() {
+        assert_eq!(posix("/a").join("/b").as_str(), "/a/b");
+        assert_eq!(posix("a").join("../../b").as_str(), "../b");
+        assert_eq!(posix("").join("").as_str(), ".");
+        assert_eq!(posix("/a/b").join("").as_str(), "/a/b");
+        assert_eq!(win32("C:\\a").join("b/c").as_str(), "C:\\a\\b\\c");
+        assert_eq!(
+            win32("//server").join("share").as_str(),
+            "\\\\server\\share\\"
+        );
+    }
+
+    #[test]
+    fn parent_and_file_name() {
+        for (path, parent, name) in [
+            ("/a/b/", Some("/a"), Some("b")),
+            ("/a", Some("/"), Some("a")),
+            ("/", None, None),
+            ("a", Some("."), Some("a")),
+            ("a/b", Some("a"), Some("b")),
+            ("", None, None),
+        ] {
+            assert_eq!(posix(path).parent().map(|p| p.as_str()), parent, "{path}");
+            assert_eq!(posix(path).file_name(), name, "{path}");
+        }
+        for (path, parent, name) in [
+            ("C:\\a", Some("C:\\"), Some("a")),
+            ("C:a", Some("C:"), Some("a")),
+            ("C:\\", None, None),
+            (
+                "\\\\server\\share\\a",
+                Some("\\\\server\\share\\"),
+                Some("a"),
+            ),
+            ("a/b\\c", Some("a/b"), Some("c")),
+        ] {
+            assert_eq!(win32(path).parent().map(|p| p.as_str()), parent, "{path}");
+            assert_eq!(win32(path).file_name(), name, "{path}");
+        }
+    }
+
+    #[test]
+    fn extension() {
+        for (path, stem, extension) in [
+            ("a/b.txt", Some("b"), Some("txt")),
+            ("a.b.c", Some("a.b"), Some("c")),
+            (".bashrc", Some(".bashrc"), None),
+            ("a.", Some("a"), Some("")),
+            ("..", Some(".."), None),
+            ("/", None, None),
+        ] {
+            assert_eq!(posix(path).file_stem(), stem, "{path}");
+            assert_eq!(posix(path).extension(), extension, "{path}");
+        }
+        assert_eq!(posix("a/b.txt").with_extension("csv").as_str(), "a/b.csv");
+        assert_eq!(posix("a/b/").with_extension("csv").as_str(), "a/b.csv");
+        assert_eq!(
+            posix("a/.env").with_extension("local").as_str(),
+            "a/.env.local"
+        );
+        assert_eq!(posix("a/b.tar.gz").with_extension("").as_str(), "a/b.tar");
+        assert_eq!(posix("/").with_extension("csv").as_str(), "/");
+    }
+
+    #[test]
+    fn components_and_roots() {
+        assert_eq!(
+            posix("./a/../b/").components(),
+            vec![
+                Component::CurDir,
+                Component::Normal("a"),
+                Component::ParentDir,
+                Component::Normal("b")
+            ]
+        );
+        assert_eq!(
+            win32("C:\\a/./b").components(),
+            vec![
+                Component::Prefix("C:"),
+                Component::RootDir,
+                Component::Normal("a"),
+                Component::Normal("b")
+            ]
+        );
+        assert!(posix("/a").is_absolute());
+        assert!(!posix("a").is_absolute());
+        assert!(win32("C:/a").is_absolute());
+        assert!(win32("\\a").is_absolute());
+        assert!(!win32("C:a").is_absolute());
+        assert_eq!(
+            PathStyle::Windows
+                .split_list("C:\\bin;;D:\\tools")
+                .collect::<Vec<_>>(),
+            ["C:\\bin", "D:\\tools"]
+        );
+    }
+}
@@ -19,6 +19,12 @@ extern "C" {
     #[wasm_bindgen(thread_local_v2, js_name = "sep")]
     pub static SEP: String;
 
+    /// Returns the last portion of a `path`. Trailing directory separators are ignored.
+    ///
+    /// Ref: <http://v1.tauri.app/v1/api/js/path#basename>
+    #[wasm_bindgen(catch)]
+    pub async fn basename(path: &str, ext: Option<String>) -> Result<JsString, JsValue>;
+
     /// Returns the directory name of a path.
     /// Trailing directory separators are ignored.
     ///
@@ -0,0 +1,697 @@
+//! Record and replay IPC sessions.
+//!
+//! While [recording](start_recording), every [`invoke`](crate::tauri::invoke) call,
+//! [emitted](crate::event::emit) and received event,
+//! and [`fs`](crate::fs), [`dialog`](crate::dialog), [`http::fetch`](crate::http::fetch)
+//! and [`RawClientWrapper`](crate::http::raw::RawClientWrapper) call
+//! is stored with its timestamp in a [`Session`], which can be saved as JSON.
+//!
+//! A [`Session`] can then be [replayed](start_replay) against a fake backend:
+//! calls are answered with the recorded results (in the recorded order) without reaching Tauri,
+//! and the recorded received events are sent to the matching listeners.
+//!
+//! ```rust,no_run
+//! use tauri_global_sys::session;
+//!
+//! # async fn run() -> Result<(), tauri_global_sys::Error> {
+//! session::start_recording();
+//! // ... reproduce the bug
+//! let fixture = session::stop_recording().unwrap_or_default().to_json()?;
+//!
+//! // later, in a test build
+//! session::start_replay(session::Session::from_json(&fixture)?);
+//! # Ok(())
+//! # }
+//! ```
+use std::{cell::RefCell, collections::VecDeque, future::Future};
+
+use js_sys::{Array, Object, Uint8Array};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// The result of a recorded call.
+///
+/// The error is stored as its message, or as JSON if it was an [`Error::Invoke`](crate::Error::Invoke).
+pub type CallResult = Result<Value, Value>;
+
+/// A recorded IPC interaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum IpcCall {
+    /// A [`tauri::invoke`](crate::tauri::invoke) call.
+    Invoke {
+        command: String,
+        args: Value,
+        result: CallResult,
+    },
+    /// An event emitted with [`event::emit`](crate::event::emit).
+    Emit { event: String, payload: Value },
+    /// An event received by a listener.
+    Received { event: String, payload: Value },
+    /// A call to a module function like `fs.readTextFile`.
+    Module {
+        module: String,
+        function: String,
+        args: Value,
+        result: CallResult,
+    },
+}
+
+impl IpcCall {
+    /// Whether `self` is the same call as `other`, ignoring the results.
+    fn same_call(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Invoke { command, args, .. },
+                Self::Invoke {
+                    command: other_command,
+                    args: other_args,
+                    ..
+                },
+            ) => command == other_command && args == other_args,
+            (
+                Self::Module {
+                    module,
+                    function,
+                    args,
+                    ..
+                },
+                Self::Module {
+                    module: other_module,
+                    function: other_function,
+                    args: other_args,
+                    ..
+                },
+            ) => module == other_module && function == other_function && args == other_args,
+            (a @ Self::Emit { .. }, b @ Self::Emit { .. }) => a == b,
+            _ => false,
+        }
+    }
+
+    fn result(&self) -> Option<&CallResult> {
+        match self {
+            Self::Invoke { result, .. } | Self::Module { result, .. } => Some(result),
+            _ => None,
+        }
+    }
+}
+
+/// A [`IpcCall`] with the number of milliseconds elapsed since the start of the recording.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionEntry {
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub call: IpcCall,
+}
+
+/// A recorded IPC session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Session {
+    pub entries: Vec<SessionEntry>,
+}
+
+impl Session {
+    pub fn to_json(&self) -> crate::Result<String> {
+        serde_json::to_string_pretty(self).map_err(crate::Error::from)
+    }
+
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        serde_json::from_str(json).map_err(crate::Error::from)
+    }
+}
+
+type ReplayHandler = Box<dyn FnMut(JsValue) -> bool>;
+
+struct ReplayListener {
+    event: String,
+    handler: ReplayHandler,
+}
+
+/// Answers the calls with the entries of a [`Session`].
+#[derive(Default)]
+struct Replayer {
+    entries: VecDeque<SessionEntry>,
+    listeners: Vec<ReplayListener>,
+}
+
+impl Replayer {
+    fn new(session: Session) -> Self {
+        Self {
+            entries: session.entries.into(),
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Consume the entries up to the recorded `call`,
+    /// returning its recorded result and the events received before it.
+    ///
+    /// Nothing is consumed if the next recorded call isn't `call`.
+    fn next_call(&mut self, call: &IpcCall) -> crate::Result<(Option<CallResult>, Vec<IpcCall>)> {
+        let Some(index) = self
+            .entries
+            .iter()
+            .position(|entry| !matches!(entry.call, IpcCall::Received { .. }))
+        else {
+            return Err(crate::Error::SessionReplay(format!(
+                "the session has no entries left for {call:?}"
+            )));
+        };
+        if !self.entries[index].call.same_call(call) {
+            return Err(crate::Error::SessionReplay(format!(
+                "expected {:?} but got {call:?}",
+                self.entries[index].call
+            )));
+        }
+        let mut received = self
+            .entries
+            .drain(..=index)
+            .map(|entry| entry.call)
+            .collect::<Vec<_>>();
+        let result = received.pop().and_then(|call| call.result().cloned());
+        Ok((result, received))
+    }
+
+    /// Consume the received events at the front of the session.
+    fn next_received(&mut self) -> Vec<IpcCall> {
+        let mut received = Vec::new();
+        while let Some(entry) = self.entries.pop_front() {
+            if let IpcCall::Received { .. } = entry.call {
+                received.push(entry.call);
+            } else {
+                self.entries.push_front(entry);
+                break;
+            }
+        }
+        received
+    }
+}
+
+#[derive(Default)]
+enum State {
+    #[default]
+    Idle,
+    Recording {
+        start: f64,
+        entries: Vec<SessionEntry>,
+        /// The ids of the listeners which received the last recorded event.
+        receivers: Vec<f64>,
+    },
+    Replaying(Replayer),
+}
+
+thread_local! {
+    static STATE: RefCell<State> = const { RefCell::new(State::Idle) };
+}
+
+/// Start recording a new session, dropping the current one if any.
+pub fn start_recording() {
+    STATE.with_borrow_mut(|state| {
+        *state = State::Recording {
+            start: js_sys::Date::now(),
+            entries: Vec::new(),
+            receivers: Vec::new(),
+        }
+    });
+}
+
+/// Stop the recording and return the recorded session.
+///
+/// Returns [`None`] if nothing was being recorded.
+pub fn stop_recording() -> Option<Session> {
+    STATE.with_borrow_mut(|state| match std::mem::take(state) {
+        State::Recording { entries, .. } => Some(Session { entries }),
+        other => {
+            *state = other;
+            None
+        }
+    })
+}
+
+pub fn is_recording() -> bool {
+    STATE.with_borrow(|state| matches!(state, State::Recording { .. }))
+}
+
+/// Start replaying `session`, stopping any recording or replay in progress.
+///
+/// The received events at the front of the session are sent on the next call or with [`flush_events`].
+pub fn start_replay(session: Session) {
+    STATE.with_borrow_mut(|state| *state = State::Replaying(Replayer::new(session)));
+}
+
+/// Stop the replay and return the entries that haven't been replayed.
+pub fn stop_replay() -> Option<Session> {
+    STATE.with_borrow_mut(|state| match std::mem::take(state) {
+        State::Replaying(replayer) => Some(Session {
+            entries: replayer.entries.into(),
+        }),
+        other => {
+            *state = other;
+            None
+        }
+    })
+}
+
+pub fn is_replaying() -> bool {
+    STATE.with_borrow(|state| matches!(state, State::Replaying(_)))
+}
+
+/// Send the received events at the front of the replayed session to their listeners.
+pub fn flush_events() {
+    if let Some(received) = with_replayer(Replayer::next_received) {
+        dispatch(received);
+    }
+}
+
+/// Send `received` events to the listeners of the replayed session.
+fn dispatch(received: Vec<IpcCall>) {
+    dispatch_with(received, json_to_js);
+}
+
+/// [`dispatch`], converting the events with `to_js`.
+///
+/// The handlers are called without borrowing the session state.
+fn dispatch_with(received: Vec<IpcCall>, to_js: impl Fn(&Value) -> crate::Result<JsValue>) {
+    if received.is_empty() {
+        return;
+    }
+    let Some(mut listeners) = with_replayer(|replayer| std::mem::take(&mut replayer.listeners))
+    else {
+        return;
+    };
+    for call in received {
+        let IpcCall::Received { event, payload } = call else {
+            continue;
+        };
+        let mut object = Map::new();
+        object.insert("event".into(), Value::String(event.clone()));
+        object.insert("id".into(), Value::Number(0.into()));
+        object.insert("payload".into(), payload);
+        object.insert("windowLabel".into(), Value::Null);
+        let object = Value::Object(object);
+        listeners.retain_mut(|listener| {
+            if listener.event != event {
+                return true;
+            }
+            to_js(&object)
+                .map(|value| (listener.handler)(value))
+                .unwrap_or(true)
+        });
+    }
+    with_replayer(|replayer| {
+        // Keep the listeners registered by the handlers.
+        listeners.append(&mut replayer.listeners);
+        replayer.listeners = listeners;
+    });
+}
+
+fn with_replayer<R>(f: impl FnOnce(&mut Replayer) -> R) -> Option<R> {
+    STATE.with_borrow_mut(|state| match state {
+        State::Replaying(replayer) => Some(f(replayer)),
+        _ => None,
+    })
+}
+
+fn record(call: impl FnOnce() -> IpcCall) {
+    STATE.with_borrow_mut(|state| {
+        if let State::Recording { start, entries, .. } = state {
+            entries.push(SessionEntry {
+                timestamp: js_sys::Date::now() - *start,
+                call: call(),
+            });
+        }
+    });
+}
+
+/// Replay `call` if a session is being replayed.
+fn replay(call: IpcCall) -> Option<crate::Result<JsValue>> {
+    let replayed = with_replayer(|replayer| replayer.next_call(&call))?;
+    Some(replayed.and_then(|(result, received)| {
+        dispatch(received);
+        match result {
+            Some(Ok(value)) => json_to_js(&value),
+            Some(Err(Value::String(message))) => Err(crate::Error::tauri(message)),
+            Some(Err(error)) => Err(crate::Error::Invoke(json_to_js(&error)?)),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }))
+}
+
+fn call_result(result: &crate::Result<JsValue>) -> CallResult {
+    match result {
+        Ok(value) => Ok(js_to_json(value)),
+        Err(crate::Error::Tauri(message)) => Err(Value::String(message.clone())),
+        Err(crate::Error::Invoke(error)) => Err(js_to_json(error)),
+        Err(error) => Err(Value::String(error.to_string())),
+    }
+}
+
+/// Convert a JS value to JSON, byte arrays become arrays of numbers.
+pub(crate) fn js_to_json(value: &JsValue) -> Value {
+    if value.is_null() || value.is_undefined() {
+        Value::Null
+    } else if let Some(boolean) = value.as_bool() {
+        Value::Bool(boolean)
+    } else if let Some(number) = value.as_f64() {
+        Number::from_f64(number)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    } else if let Some(string) = value.as_string() {
+        Value::String(string)
+    } else if let Some(bytes) = value.dyn_ref::<Uint8Array>() {
+        Value::Array(bytes.to_vec().into_iter().map(Value::from).collect())
+    } else if let Some(array) = value.dyn_ref::<Array>() {
+        Value::Array(array.iter().map(|item| js_to_json(&item)).collect())
+    } else if let Some(object) = value.dyn_ref::<Object>() {
+        Value::Object(
+            Object::entries(object)
+                .iter()
+                .filter_map(|entry| {
+                    let entry = entry.dyn_into::<Array>().ok()?;
+                    Some((entry.get(0).as_string()?, js_to_json(&entry.get(1))))
+                })
+                .collect(),
+        )
+    } else {
+        Value::Null
+    }
+}
+
+fn json_to_js(value: &Value) -> crate::Result<JsValue> {
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// Run an `invoke` call through the session.
+pub(crate) async fn invoke<I, Fut>(command: &str, args: &I, call: Fut) -> crate::Result<JsValue>
+where
+    I: Serialize + ?Sized,
+    Fut: Future<Output = crate::Result<JsValue>>,
+{
+    let mode = Mode::current();
+    if mode == Mode::Idle {
+        return call.await;
+    }
+    let call_with = |args, result| IpcCall::Invoke {
+        command: command.to_string(),
+        args,
+        result,
+    };
+    // The args are recorded as `null` when they can't be converted to JSON (like maps with non-string keys),
+    // so a session never fails a call which succeeds without it.
+    let args = match serde_json::to_value(args) {
+        Ok(args) => args,
+        Err(_err) => {
+            #[cfg(feature = "log")]
+            log::warn!("Cannot record the args of `{command}` as JSON: {_err}");
+            Value::Null
+        }
+    };
+    run(mode, call_with, args, call).await
+}
+
+/// Run a module function call (like `fs.readTextFile`) through the session.
+pub(crate) async fn module_call<Fut>(
+    module: &str,
+    function: &str,
+    args: &[&JsValue],
+    call: Fut,
+) -> crate::Result<JsValue>
+where
+    Fut: Future<Output = Result<JsValue, JsValue>>,
+{
+    let mode = Mode::current();
+    if mode == Mode::Idle {
+        return Ok(call.await?);
+    }
+    let call_with = |args, result| IpcCall::Module {
+        module: module.to_string(),
+        function: function.to_string(),
+        args,
+        result,
+    };
+    let args = Value::Array(args.iter().map(|arg| js_to_json(arg)).collect());
+    run(mode, call_with, args, async { Ok(call.await?) }).await
+}
+
+async fn run<Fut>(
+    mode: Mode,
+    call_with: impl Fn(Value, CallResult) -> IpcCall,
+    args: Value,
+    call: Fut,
+) -> crate::Result<JsValue>
+where
+    Fut: Future<Output = crate::Result<JsValue>>,
+{
+    if mode == Mode::Replaying {
+        if let Some(replayed) = replay(call_with(args.clone(), Ok(Value::Null))) {
+            return replayed;
+        }
+    }
+    let result = call.await;
+    record(|| call_with(args, call_result(&result)));
+    result
+}
+
+/// Record or replay an emitted event.
+///
+/// Returns `true` if the event was replayed and must not be sent to Tauri.
+pub(crate) fn emit(event: &str, payload: &JsValue) -> crate::Result<bool> {
+    match Mode::current() {
+        Mode::Idle => Ok(false),
+        Mode::Recording => {
+            record(|| IpcCall::Emit {
+                event: event.to_string(),
+                payload: js_to_json(payload),
+            });
+            Ok(false)
+        }
+        Mode::Replaying => replay(IpcCall::Emit {
+            event: event.to_string(),
+            payload: js_to_json(payload),
+        })
+        .transpose()
+        .map(|replayed| replayed.is_some()),
+    }
+}
+
+/// Record an event received by a listener.
+///
+/// Tauri calls every listener of an event with its own copy, so only the first copy is recorded:
+/// the replay sends each recorded event to all the matching listeners.
+pub(crate) fn received(event: &JsValue) {
+    if Mode::current() != Mode::Recording {
+        return;
+    }
+    let get = |key: &str| js_sys::Reflect::get(event, &JsValue::from_str(key)).ok();
+    let listener = get("id").and_then(|id| id.as_f64());
+    let call = IpcCall::Received {
+        event: get("event").and_then(|e| e.as_string()).unwrap_or_default(),
+        payload: get("payload").map(|p| js_to_json(&p)).unwrap_or_default(),
+    };
+    STATE.with_borrow_mut(|state| {
+        if let State::Recording {
+            start,
+            entries,
+            receivers,
+        } = state
+        {
+            let timestamp = js_sys::Date::now() - *start;
+            record_received(entries, receivers, timestamp, listener, call);
+        }
+    });
+}
+
+/// Record the `call` received by the `listener`,
+/// unless it is the copy of the last recorded event sent to another listener.
+fn record_received(
+    entries: &mut Vec<SessionEntry>,
+    receivers: &mut Vec<f64>,
+    timestamp: f64,
+    listener: Option<f64>,
+    call: IpcCall,
+) {
+    let copy = listener.is_some_and(|listener| {
+        entries.last().is_some_and(|last| last.call == call) && !receivers.contains(&listener)
+    });
+    if copy {
+        receivers.extend(listener);
+        return;
+    }
+    receivers.clear();
+    receivers.extend(listener);
+    entries.push(SessionEntry { timestamp, call });
+}
+
+/// Register a listener on the replayed session.
+///
+/// `handler` returns `false` when it doesn't want to receive events anymore.
+///
+/// Returns `false` if no session is being replayed.
+pub(crate) fn replay_listen(event: &str, handler: impl FnMut(JsValue) -> bool + 'static) -> bool {
+    with_replayer(|replayer| {
+        replayer.listeners.push(ReplayListener {
+            event: event.to_string(),
+            handler: Box::new(handler),
+        })
+    })
+    .is_some()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Idle,
+    Recording,
+    Replaying,
+}
+
+impl Mode {
+    fn current() -> Self {
+        STATE.with_borrow(|state| match state {
+            State::Idle => Self::Idle,
+            State::Recording { .. } => Self::Recording,
+            State::Replaying(_) => Self::Replaying,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use serde_json::json;
+    use wasm_bindgen::JsValue;
+
+    use super::{
+        dispatch_with, record_received, replay_listen, start_replay, stop_replay, with_replayer,
+        IpcCall, Replayer, Session, SessionEntry,
+    };
+
+    fn session() -> Session {
+        Session {
+            entries: vec![
+                SessionEntry {
+                    timestamp: 0.0,
+                    call: IpcCall::Invoke {
+                        command: "greet".into(),
+                        args: json!({ "name": "tony" }),
+                        result: Ok(json!("Hello, tony!")),
+                    },
+                },
+                SessionEntry {
+                    timestamp: 12.5,
+                    call: IpcCall::Received {
+                        event: "sync://users".into(),
+                        payload: json!([1, 2]),
+                    },
+                },
+                SessionEntry {
+                    timestamp: 20.0,
+                    call: IpcCall::Module {
+                        module: "fs".into(),
+                        function: "exists".into(),
+                        args: json!(["db.sqlite", { "dir": 22 }]),
+                        result: Err(json!("path not allowed on the configured scope")),
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn session_json() {
+        let session = session();
+        let json = serde_json::to_value(&session).unwrap();
+        assert_eq!(
+            json["entries"][0],
+            json!({
+                "timestamp": 0.0,
+                "kind": "invoke",
+                "command": "greet",
+                "args": { "name": "tony" },
+                "result": { "Ok": "Hello, tony!" }
+            })
+        );
+        assert_eq!(
+            Session::from_json(&session.to_json().unwrap()).unwrap(),
+            session
+        );
+    }
+
+    #[test]
+    fn replay_in_order() {
+        let mut replayer = Replayer::new(session());
+        let (result, received) = replayer
+            .next_call(&IpcCall::Invoke {
+                command: "greet".into(),
+                args: json!({ "name": "tony" }),
+                result: Ok(json!(null)),
+            })
+            .unwrap();
+        assert_eq!(result, Some(Ok(json!("Hello, tony!"))));
+        assert!(received.is_empty());
+
+        let exists = IpcCall::Module {
+            module: "fs".into(),
+            function: "exists".into(),
+            args: json!(["db.sqlite", { "dir": 22 }]),
+            result: Ok(json!(null)),
+        };
+        let other = IpcCall::Module {
+            module: "fs".into(),
+            function: "exists".into(),
+            args: json!(["other.sqlite", { "dir": 22 }]),
+            result: Ok(json!(null)),
+        };
+        assert!(replayer.next_call(&other).is_err());
+        let (result, received) = replayer.next_call(&exists).unwrap();
+        assert_eq!(
+            result,
+            Some(Err(json!("path not allowed on the configured scope")))
+        );
+        assert_eq!(received.len(), 1);
+        assert!(replayer.next_call(&exists).is_err());
+    }
+
+    #[test]
+    fn two_listeners() {
+        let users = || IpcCall::Received {
+            event: "sync://users".into(),
+            payload: json!([1, 2]),
+        };
+        // One event sent to the listeners 1 and 2, then again to the listener 1 only.
+        let mut entries = Vec::new();
+        let mut receivers = Vec::new();
+        for (timestamp, listener) in [(1.0, 1.0), (1.0, 2.0), (5.0, 1.0)] {
+            record_received(
+                &mut entries,
+                &mut receivers,
+                timestamp,
+                Some(listener),
+                users(),
+            );
+        }
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.timestamp)
+                .collect::<Vec<_>>(),
+            vec![1.0, 5.0]
+        );
+
+        start_replay(Session {
+            entries: entries.split_off(1),
+        });
+        let calls = Rc::new(Cell::new(0));
+        for _ in 0..2 {
+            let calls = calls.clone();
+            replay_listen("sync://users", move |_| {
+                calls.set(calls.get() + 1);
+                true
+            });
+        }
+        let received = with_replayer(Replayer::next_received).unwrap();
+        dispatch_with(received, |_| Ok(JsValue::NULL));
+        assert_eq!(calls.get(), 2);
+        assert_eq!(stop_replay(), Some(Session::default()));
+    }
+}
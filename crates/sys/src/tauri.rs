@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsValue;
 
+pub mod asset;
 /// `wasm-bindgen` raw bindings
 pub mod raw;
+pub mod stream;
+
+pub use asset::{AssetUrl, ObjectUrl};
+pub use stream::{invoke_stream, InvokeStream};
 
 /// Send a message to the backend
 pub async fn invoke<I, O>(command: &str, args: &I) -> Result<O, crate::Error>
@@ -10,19 +15,24 @@ where
     I: Serialize,
     O: for<'a> Deserialize<'a>,
 {
-    match raw::invoke(command, serde_wasm_bindgen::to_value(args)?).await {
-        Ok(result) => serde_wasm_bindgen::from_value(result).map_err(crate::Error::from),
-        Err(error) => {
-            if let Some(err) = error.as_string() {
-                Err(crate::Error::tauri(err))
-            } else {
-                Err(crate::Error::Invoke(error))
-            }
-        }
-    }
+    let result = crate::session::invoke(command, args, async {
+        raw::invoke(command, serde_wasm_bindgen::to_value(args)?)
+            .await
+            .map_err(|error| {
+                if let Some(err) = error.as_string() {
+                    crate::Error::tauri(err)
+                } else {
+                    crate::Error::Invoke(error)
+                }
+            })
+    })
+    .await?;
+    serde_wasm_bindgen::from_value(result).map_err(crate::Error::from)
 }
 
 /// Convert a device file path to an URL that can be loaded by the webview.
+///
+/// See [`AssetUrl`] for the typed version.
 pub fn convert_file_src(file_path: &str, protocol: Option<&str>) -> String {
     raw::convertFileSrc(file_path, protocol)
 }
@@ -0,0 +1,280 @@
+//! URLs of local files served to the webview by the asset protocol.
+//!
+//! The protocol must be enabled, with the files in its scope, in tauri.conf.json,
+//! and the CSP must allow `asset:` and `https://asset.localhost` (the URL used on Windows):
+//!
+//! ```json
+//! {
+//!   "tauri": {
+//!     "allowlist": {
+//!       "protocol": {
+//!         "asset": true,
+//!         "assetScope": ["$APPDATA/**"]
+//!       }
+//!     },
+//!     "security": {
+//!       "csp": "default-src 'self'; img-src 'self' asset: https://asset.localhost; media-src 'self' asset: https://asset.localhost; connect-src 'self' asset: https://asset.localhost"
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The `connect-src` is needed by [`AssetUrl::fetch_blob`] and by the error diagnostics.
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use futures::channel::oneshot;
+use js_sys::Promise;
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, EventTarget, HtmlImageElement, HtmlMediaElement, Response, Url};
+
+use crate::{
+    fs::BaseDirectory,
+    path::{PathStyle, TauriPath},
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch(url: &str) -> Promise;
+}
+
+/// The error for an asset answered with `status`: the asset protocol answers 403 outside of its scope.
+fn status_error(path: &str, url: &str, status: u16) -> crate::Error {
+    match status {
+        403 => crate::Error::AssetScopeDenied(path.into()),
+        status => crate::Error::AssetLoad {
+            url: url.into(),
+            status: Some(status),
+        },
+    }
+}
+
+/// The URL of a local file, see [`convert_file_src`](super::convert_file_src).
+///
+/// ```rust,no_run
+/// use tauri_global_sys::{fs::BaseDirectory, tauri::asset::AssetUrl};
+/// use web_sys::HtmlImageElement;
+///
+/// # async fn run(avatar: HtmlImageElement) -> Result<(), tauri_global_sys::Error> {
+/// let url = AssetUrl::from_base_dir(BaseDirectory::AppData, "avatars/me.png").await?;
+/// url.load_image(&avatar).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssetUrl {
+    path: String,
+    protocol: Option<String>,
+    url: String,
+}
+
+impl fmt::Display for AssetUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+impl AssetUrl {
+    /// The URL of the absolute `path` with the `asset` protocol.
+    pub fn new(path: impl Into<String>) -> Self {
+        let path = path.into();
+        Self {
+            url: super::convert_file_src(&path, None),
+            path,
+            protocol: None,
+        }
+    }
+
+    /// The URL of `relative` in `base_dir`, see [`BaseDirectory::resolve`].
+    pub async fn from_base_dir(base_dir: BaseDirectory, relative: &str) -> crate::Result<Self> {
+        let dir = base_dir.resolve().await?;
+        let path = TauriPath::with_style(&dir, PathStyle::host()).join(relative);
+        Ok(Self::new(path.into_string()))
+    }
+
+    /// Use a custom protocol registered by the backend instead of `asset`.
+    pub fn protocol(mut self, protocol: impl Into<String>) -> Self {
+        let protocol = protocol.into();
+        self.url = super::convert_file_src(&self.path, Some(&protocol));
+        self.protocol = Some(protocol);
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+
+    async fn fetch(&self) -> crate::Result<Response> {
+        let response =
+            JsFuture::from(fetch(&self.url))
+                .await
+                .map_err(|_| crate::Error::AssetLoad {
+                    url: self.url.clone(),
+                    status: None,
+                })?;
+        let response: Response = response.unchecked_into();
+        if response.ok() {
+            Ok(response)
+        } else {
+            Err(status_error(&self.path, &self.url, response.status()))
+        }
+    }
+
+    /// Fetch the asset again to find out why an element failed to load it.
+    async fn diagnose(&self) -> crate::Error {
+        match self.fetch().await {
+            Ok(_) => crate::Error::AssetLoad {
+                url: self.url.clone(),
+                status: None,
+            },
+            Err(err) => err,
+        }
+    }
+
+    /// Fetch the whole asset.
+    ///
+    /// Fails with [`Error::AssetScopeDenied`](crate::Error::AssetScopeDenied) if the path is outside of the asset scope.
+    pub async fn fetch_blob(&self) -> crate::Result<Blob> {
+        let response = self.fetch().await?;
+        Ok(JsFuture::from(response.blob()?).await?.unchecked_into())
+    }
+
+    /// Fetch the whole asset into a `blob:` URL, which doesn't need the CSP to allow the asset protocol.
+    pub async fn object_url(&self) -> crate::Result<ObjectUrl> {
+        let blob = self.fetch_blob().await?;
+        Ok(ObjectUrl(Url::create_object_url_with_blob(&blob)?))
+    }
+
+    /// Set the `src` of `image` and wait until it is loaded.
+    pub async fn load_image(&self, image: &HtmlImageElement) -> crate::Result<()> {
+        let loaded = wait_for(image, "load", || image.set_src(&self.url)).await?;
+        if loaded {
+            Ok(())
+        } else {
+            Err(self.diagnose().await)
+        }
+    }
+
+    /// Set the `src` of `media`, an audio or a video, and wait until its metadata are loaded.
+    pub async fn load_media(&self, media: &HtmlMediaElement) -> crate::Result<()> {
+        let loaded = wait_for(media, "loadedmetadata", || {
+            media.set_src(&self.url);
+            media.load();
+        })
+        .await?;
+        if loaded {
+            Ok(())
+        } else {
+            Err(self.diagnose().await)
+        }
+    }
+}
+
+/// The `event` and `error` listeners of [`wait_for`], removed on drop,
+/// so they don't outlive their closures even if the wait is cancelled.
+struct Listeners<'a> {
+    target: &'a EventTarget,
+    event: &'a str,
+    on_load: Closure<dyn FnMut()>,
+    on_error: Closure<dyn FnMut()>,
+}
+
+impl Listeners<'_> {
+    fn add(&self) -> crate::Result<()> {
+        self.target
+            .add_event_listener_with_callback(self.event, self.on_load.as_ref().unchecked_ref())?;
+        self.target
+            .add_event_listener_with_callback("error", self.on_error.as_ref().unchecked_ref())?;
+        Ok(())
+    }
+}
+
+impl Drop for Listeners<'_> {
+    fn drop(&mut self) {
+        // Removing a listener which wasn't added is a no-op.
+        for (event, listener) in [(self.event, &self.on_load), ("error", &self.on_error)] {
+            let _re = self
+                .target
+                .remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+            #[cfg(feature = "log")]
+            if let Err(err) = _re {
+                log::error!("Error on removing the {event} listener ({err:?})");
+            }
+        }
+    }
+}
+
+/// Call `start` and wait for the `event` or the `error` event of `target`, resolving to `false` on the latter.
+async fn wait_for(target: &EventTarget, event: &str, start: impl FnOnce()) -> crate::Result<bool> {
+    let (tx, rx) = oneshot::channel();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let listener = |loaded: bool| {
+        let tx = tx.clone();
+        Closure::<dyn FnMut()>::new(move || {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(loaded);
+            }
+        })
+    };
+    let listeners = Listeners {
+        target,
+        event,
+        on_load: listener(true),
+        on_error: listener(false),
+    };
+    listeners.add()?;
+    start();
+    let loaded = rx.await;
+    drop(listeners);
+    Ok(loaded?)
+}
+
+/// A `blob:` URL created by [`AssetUrl::object_url`], revoked on drop.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ObjectUrl(String);
+
+impl ObjectUrl {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ObjectUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Drop for ObjectUrl {
+    fn drop(&mut self) {
+        let _re = Url::revoke_object_url(&self.0);
+        #[cfg(feature = "log")]
+        if let Err(err) = _re {
+            log::error!("Error on revoking the object URL {:?} ({err:?})", self.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::status_error;
+    use crate::ErrorKind;
+
+    #[test]
+    fn status_errors() {
+        let denied = status_error("/etc/passwd", "asset://localhost/%2Fetc%2Fpasswd", 403);
+        assert!(matches!(&denied, crate::Error::AssetScopeDenied(path) if path == "/etc/passwd"));
+        assert_eq!(denied.kind(), ErrorKind::ScopeForbidden);
+        let missing = status_error("/a.png", "asset://localhost/%2Fa.png", 404);
+        assert_eq!(missing.kind(), ErrorKind::NotFound);
+        assert_eq!(
+            missing.to_string(),
+            "failed to load the asset `asset://localhost/%2Fa.png` (status 404)"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Streaming results from backend commands.
+//!
+//! Tauri v1 has no `Channel` type, so [`invoke_stream`] generates a unique event name,
+//! passes it to the command in the [`STREAM_CHANNEL_ARG`] argument and listens to it.
+//! The command then emits [`StreamMessage`]s on that event until it sends [`StreamMessage::End`]
+//! or [`StreamMessage::Error`].
+//!
+//! The `tauri-global-sys-backend` crate provides a helper that emits with the same protocol.
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{channel::oneshot, FutureExt, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+
+use crate::event::{listen, Listen};
+
+/// The name of the argument holding the stream event name.
+///
+/// On the backend, it is received as the `stream_channel: String` command argument.
+pub const STREAM_CHANNEL_ARG: &str = "streamChannel";
+
+/// A message emitted by the backend on a stream event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum StreamMessage<T> {
+    /// A new item.
+    Item(T),
+    /// The stream is finished.
+    End,
+    /// The stream failed, no more items will be sent.
+    Error(String),
+}
+
+/// A [`Stream`] of the items sent by a command invoked with [`invoke_stream`].
+///
+/// The underlying event listener is removed when this is dropped.
+pub struct InvokeStream<T> {
+    events: Listen<StreamMessage<T>>,
+    invoke: Option<oneshot::Receiver<crate::Result<()>>>,
+    channel: String,
+    done: bool,
+}
+
+impl<T> InvokeStream<T> {
+    /// The event name this stream listens to.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+}
+
+impl<T> Stream for InvokeStream<T> {
+    type Item = crate::Result<T>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        if let Poll::Ready(message) = self.events.poll_next_unpin(cx) {
+            return match message.map(|event| event.payload) {
+                Some(StreamMessage::Item(item)) => Poll::Ready(Some(Ok(item))),
+                Some(StreamMessage::Error(error)) => {
+                    self.done = true;
+                    Poll::Ready(Some(Err(crate::Error::tauri(error))))
+                }
+                Some(StreamMessage::End) | None => {
+                    self.done = true;
+                    Poll::Ready(None)
+                }
+            };
+        }
+        if let Some(invoke) = self.invoke.as_mut() {
+            if let Poll::Ready(result) = invoke.poll_unpin(cx) {
+                self.invoke = None;
+                // The command returning successfully doesn't end the stream, only `End` does.
+                if let Ok(Err(error)) = result {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(error)));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Invoke a streaming `command`.
+///
+/// `args` must serialize to an object (or `()`), the [`STREAM_CHANNEL_ARG`] argument is added to it.
+pub async fn invoke_stream<A, T>(command: &str, args: &A) -> crate::Result<InvokeStream<T>>
+where
+    A: Serialize,
+    T: DeserializeOwned + 'static,
+{
+    let channel = format!("invoke-stream:{}", Uuid::new_v4());
+    let args = serde_wasm_bindgen::to_value(args)?;
+    let args = if args.is_undefined() || args.is_null() {
+        js_sys::Object::new()
+    } else {
+        args.dyn_into::<js_sys::Object>()
+            .map_err(|_| crate::Error::InvalidType("an object for the command args".into()))?
+    };
+    js_sys::Reflect::set(
+        &args,
+        &JsValue::from_str(STREAM_CHANNEL_ARG),
+        &JsValue::from_str(&channel),
+    )?;
+    // Listen before invoking so that no item is missed.
+    let events = listen::<StreamMessage<T>>(&channel).await?;
+    let (tx, rx) = oneshot::channel();
+    let command = command.to_string();
+    spawn_local(async move {
+        let result = super::raw::invoke(&command, args.into())
+            .await
+            .map(|_| ())
+            .map_err(crate::Error::from);
+        let _ = tx.send(result);
+    });
+    Ok(InvokeStream {
+        events,
+        invoke: Some(rx),
+        channel,
+        done: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::StreamMessage;
+
+    #[test]
+    fn message_ser() {
+        assert_eq!(
+            serde_json::to_value(StreamMessage::Item(4)).unwrap(),
+            json!({ "type": "item", "data": 4 })
+        );
+        assert_eq!(
+            serde_json::to_value(StreamMessage::<u8>::End).unwrap(),
+            json!({ "type": "end" })
+        );
+        assert_eq!(
+            serde_json::to_value(StreamMessage::<u8>::Error("oops".into())).unwrap(),
+            json!({ "type": "error", "data": "oops" })
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Browser timers (`setTimeout` and `requestAnimationFrame`) exposed as futures.
+//!
+//! This does not rely on any async runtime, only on the browser global functions.
+use std::{future::Future, time::Duration};
+
+use futures::{channel::oneshot, FutureExt};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &Closure<dyn FnMut()>, timeout: i32) -> i32;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(handle: i32);
+
+    #[wasm_bindgen(js_name = requestAnimationFrame)]
+    fn request_animation_frame(callback: &Closure<dyn FnMut(f64)>) -> i32;
+
+    #[wasm_bindgen(js_name = cancelAnimationFrame)]
+    fn cancel_animation_frame(handle: i32);
+}
+
+fn duration_to_millis(duration: Duration) -> i32 {
+    duration.as_millis().min(i32::MAX as u128) as i32
+}
+
+/// A future that resolves after a given [`Duration`].
+///
+/// The underlying timeout is cleared if the future is dropped before it resolves.
+pub(crate) struct Sleep {
+    rx: oneshot::Receiver<()>,
+    handle: Option<i32>,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Sleep {
+    pub(crate) fn new(duration: Duration) -> Self {
+        let (tx, rx) = oneshot::channel::<()>();
+        let closure = Closure::once(move || {
+            let _ = tx.send(());
+        });
+        let handle = set_timeout(&closure, duration_to_millis(duration));
+        Self {
+            rx,
+            handle: Some(handle),
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            clear_timeout(handle);
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        self.rx.poll_unpin(cx).map(|_| {
+            self.handle.take();
+        })
+    }
+}
+
+/// A future that resolves on the next animation frame, with the frame timestamp.
+///
+/// The frame request is cancelled if the future is dropped before it resolves.
+pub(crate) struct AnimationFrame {
+    rx: oneshot::Receiver<f64>,
+    handle: Option<i32>,
+    _closure: Closure<dyn FnMut(f64)>,
+}
+
+impl AnimationFrame {
+    pub(crate) fn new() -> Self {
+        let (tx, rx) = oneshot::channel::<f64>();
+        let closure = Closure::once(move |timestamp: f64| {
+            let _ = tx.send(timestamp);
+        });
+        let handle = request_animation_frame(&closure);
+        Self {
+            rx,
+            handle: Some(handle),
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for AnimationFrame {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            cancel_animation_frame(handle);
+        }
+    }
+}
+
+impl Future for AnimationFrame {
+    type Output = f64;
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        self.rx.poll_unpin(cx).map(|timestamp| {
+            self.handle.take();
+            timestamp.unwrap_or_default()
+        })
+    }
+}
+
+/// Wait for the given [`Duration`].
+pub(crate) fn sleep(duration: Duration) -> Sleep {
+    Sleep::new(duration)
+}
+
+/// Run `future` and fail with [`Error::Timeout`](crate::Error::Timeout) if it doesn't complete within `duration`.
+pub(crate) async fn timeout<F>(duration: Duration, future: F) -> crate::Result<F::Output>
+where
+    F: Future,
+{
+    futures::select! {
+        output = future.fuse() => Ok(output),
+        _ = sleep(duration).fuse() => Err(crate::Error::Timeout),
+    }
+}
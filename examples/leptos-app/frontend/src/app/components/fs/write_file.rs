@@ -85,7 +85,7 @@ pub fn WriteFile() -> impl IntoView {
                 WriteMode::WriteBinary2 => {
                     write_binary_file2(
                         FsBinaryFileOption {
-                            contents: content.into_bytes(),
+                            contents: content.into_bytes().into(),
                             path: path.clone(),
                         },
                         fs_file_option.into(),